@@ -0,0 +1,33 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct CrtSettings {
+    curvature: f32,
+    scanline_intensity: f32,
+    scanline_count: f32,
+    vignette: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CrtSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // Bend the picture outwards like the glass of an arcade tube
+    let centered = in.uv * 2.0 - 1.0;
+    let bent = centered * (1.0 + settings.curvature * dot(centered, centered));
+    let uv = bent * 0.5 + 0.5;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    var color = textureSample(screen_texture, texture_sampler, uv).rgb;
+
+    let scanline = sin(uv.y * settings.scanline_count * 3.14159265);
+    color *= 1.0 - settings.scanline_intensity * (0.5 - 0.5 * scanline);
+
+    let vignette = 1.0 - settings.vignette * dot(centered, centered) * 0.5;
+    color *= clamp(vignette, 0.0, 1.0);
+
+    return vec4<f32>(color, 1.0);
+}
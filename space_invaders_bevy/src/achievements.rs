@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::profiles::CurrentProfile;
+use super::{storage, EnemyKilled, GameAssets, GameplaySet, Level, Locale, RunStats, ShotMissed, WaveCleared};
+
+// One-off goals unlocked from gameplay events and kept across sessions in the profile's achievements.ron
+const ACHIEVEMENTS_FILE: &str = "achievements.ron";
+const TOAST_SECONDS: f32 = 3.0;
+const COMBO_TARGET: u32 = 20;
+const VETERAN_LEVEL: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    FirstBlood,
+    FlawlessWave,
+    ReachLevel10,
+    ChargedElite,
+    Combo20,
+}
+
+impl Achievement {
+    const ALL: &'static [Achievement] = &[
+        Achievement::FirstBlood,
+        Achievement::FlawlessWave,
+        Achievement::ReachLevel10,
+        Achievement::ChargedElite,
+        Achievement::Combo20,
+    ];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "achievement.first_blood",
+            Achievement::FlawlessWave => "achievement.flawless_wave",
+            Achievement::ReachLevel10 => "achievement.reach_level_10",
+            Achievement::ChargedElite => "achievement.charged_elite",
+            Achievement::Combo20 => "achievement.combo_20",
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(ACHIEVEMENTS_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(ACHIEVEMENTS_FILE), self, "achievements");
+    }
+
+    fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+}
+
+#[derive(Event)]
+struct AchievementUnlocked(Achievement);
+
+#[derive(Resource, Default)]
+struct AchievementsScreen {
+    open: bool,
+}
+
+#[derive(Component)]
+struct AchievementsText;
+
+#[derive(Component)]
+struct AchievementToast {
+    timer: Timer,
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        let achievements = Achievements::load(app.world.resource::<CurrentProfile>());
+        app.insert_resource(achievements)
+            .init_resource::<AchievementsScreen>()
+            .add_event::<AchievementUnlocked>()
+            .add_systems(Startup, setup_achievements_ui)
+            .add_systems(
+                Update,
+                (
+                    reload_achievements,
+                    (track_kills, track_flawless_waves, track_progress),
+                    announce_unlocks,
+                    fade_toasts,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Ui),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_achievements_screen.in_set(GameplaySet::Input),
+                    update_achievements_text.in_set(GameplaySet::Ui),
+                ),
+            );
+    }
+}
+
+// Takes the ResMut itself so checking an already unlocked achievement doesn't mark the resource changed
+fn unlock(
+    achievements: &mut ResMut<Achievements>,
+    events: &mut EventWriter<AchievementUnlocked>,
+    achievement: Achievement,
+) {
+    if !achievements.is_unlocked(achievement) {
+        achievements.unlocked.push(achievement);
+        events.send(AchievementUnlocked(achievement));
+    }
+}
+
+fn track_kills(
+    mut killed_events: EventReader<EnemyKilled>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    for kill in killed_events.read() {
+        unlock(&mut achievements, &mut unlocked_events, Achievement::FirstBlood);
+        if kill.elite && kill.charged {
+            unlock(&mut achievements, &mut unlocked_events, Achievement::ChargedElite);
+        }
+    }
+}
+
+// A wave counts as flawless when no shot left the screen between its start and its clear
+fn track_flawless_waves(
+    level: Res<Level>,
+    mut missed_events: EventReader<ShotMissed>,
+    mut cleared_events: EventReader<WaveCleared>,
+    mut missed: Local<bool>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    if level.is_changed() {
+        *missed = false;
+    }
+    if missed_events.read().count() > 0 {
+        *missed = true;
+    }
+    if cleared_events.read().count() > 0 && !*missed {
+        unlock(&mut achievements, &mut unlocked_events, Achievement::FlawlessWave);
+    }
+}
+
+fn track_progress(
+    level: Res<Level>,
+    stats: Res<RunStats>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    if level.0 >= VETERAN_LEVEL {
+        unlock(&mut achievements, &mut unlocked_events, Achievement::ReachLevel10);
+    }
+    if stats.best_combo >= COMBO_TARGET {
+        unlock(&mut achievements, &mut unlocked_events, Achievement::Combo20);
+    }
+}
+
+fn announce_unlocks(
+    mut commands: Commands,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+    achievements: Res<Achievements>,
+    profile: Res<CurrentProfile>,
+    locale: Res<Locale>,
+    assets: Res<GameAssets>,
+    toast_query: Query<(), With<AchievementToast>>,
+) {
+    let stacked = toast_query.iter().count();
+    for (index, AchievementUnlocked(achievement)) in unlocked_events.read().enumerate() {
+        println!("Achievement unlocked: {:?}", achievement);
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    format!("{} {}", locale.get("achievements.unlocked"), locale.get(achievement.label_key())),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 22.0,
+                        color: Color::GOLD,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0 + (stacked + index) as f32 * 30.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(25),
+                ..default()
+            },
+            AchievementToast {
+                timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+            },
+        ));
+    }
+    if achievements.is_changed() && !achievements.is_added() {
+        achievements.save(&profile);
+    }
+}
+
+fn reload_achievements(profile: Res<CurrentProfile>, mut achievements: ResMut<Achievements>) {
+    if profile.is_changed() && !profile.is_added() {
+        *achievements = Achievements::load(&profile);
+    }
+}
+
+// Toasts use real time so they still clear while the game is paused
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut Text, &mut AchievementToast)>,
+) {
+    for (entity, mut text, mut toast) in query.iter_mut() {
+        toast.timer.tick(time.delta());
+        let remaining = toast.timer.fraction_remaining();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(remaining);
+        }
+        if toast.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn setup_achievements_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(20.0),
+                top: Val::Percent(20.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        AchievementsText,
+    ));
+}
+
+fn toggle_achievements_screen(keyboard_input: Res<ButtonInput<KeyCode>>, mut screen: ResMut<AchievementsScreen>) {
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        screen.open = !screen.open;
+    }
+}
+
+fn update_achievements_text(
+    screen: Res<AchievementsScreen>,
+    achievements: Res<Achievements>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<AchievementsText>>,
+) {
+    if !screen.is_changed() && !achievements.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if screen.open { Visibility::Inherited } else { Visibility::Hidden };
+        let mut lines = vec![locale.get("achievements.title").to_string()];
+        for achievement in Achievement::ALL {
+            let mark = if achievements.is_unlocked(*achievement) { "[x]" } else { "[ ]" };
+            lines.push(format!("{} {}", mark, locale.get(achievement.label_key())));
+        }
+        text.sections[0].value = lines.join("\n");
+    }
+}
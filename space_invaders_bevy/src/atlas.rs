@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use super::scrap::Scrap;
+use super::{mods, Bullet, EnemyBullet, GameAssets, GameplaySet, PowerUp, WaveScoped};
+
+// Every sprite the game draws lives in one texture built at startup: the ship and invader PNGs,
+// a white cell that bullets and power-ups tint, and the explosion animation frames.
+// Sprites sharing one texture batch into a single draw call.
+const EXPLOSION_FRAMES: usize = 6;
+const EXPLOSION_FRAME_SIZE: u32 = 32;
+const EXPLOSION_FRAME_SECONDS: f32 = 0.05;
+const RING_SIZE: u32 = 32;
+const RING_THICKNESS: f32 = 3.0;
+const BUNKER_CELL_SIZE: u32 = 8;
+const TINT_CELL_SIZE: u32 = 4;
+const PLACEHOLDER_SIZE: u32 = 16;
+// Stand-in colours for sprite files that are missing or unreadable
+const PLAYER_PLACEHOLDER: [u8; 4] = [60, 220, 90, 255];
+const ENEMY_PLACEHOLDER: [u8; 4] = [230, 70, 70, 255];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SpriteKind {
+    Player,
+    Enemy,
+    // Plain white, coloured by Sprite::color
+    Tint,
+    Explosion(usize),
+    // White outline of a circle, for enemy shield rings
+    Ring,
+    // Bunker segments, whole and after their first hit; white, coloured by the palette
+    Bunker,
+    BunkerDamaged,
+}
+
+pub struct SpriteAtlas {
+    pub image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    indices: HashMap<SpriteKind, usize>,
+}
+
+#[derive(Component)]
+struct Explosion {
+    frame: usize,
+    timer: Timer,
+}
+
+pub struct AtlasPlugin;
+
+impl Plugin for AtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tint_plain_sprites.in_set(GameplaySet::Cleanup), animate_explosions.in_set(GameplaySet::Ui)),
+        );
+    }
+}
+
+impl SpriteAtlas {
+    pub fn build(world: &mut World) -> Self {
+        let mut sources = vec![
+            (SpriteKind::Player, load_png("player.png", PLAYER_PLACEHOLDER)),
+            (SpriteKind::Enemy, load_png("enemy2.png", ENEMY_PLACEHOLDER)),
+            (SpriteKind::Tint, solid_image(TINT_CELL_SIZE, TINT_CELL_SIZE)),
+            (SpriteKind::Ring, ring_image()),
+            (SpriteKind::Bunker, solid_image(BUNKER_CELL_SIZE, BUNKER_CELL_SIZE)),
+            (SpriteKind::BunkerDamaged, damaged_bunker_image()),
+        ];
+        for frame in 0..EXPLOSION_FRAMES {
+            sources.push((SpriteKind::Explosion(frame), explosion_frame(frame)));
+        }
+
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let handles: Vec<(SpriteKind, Handle<Image>)> = sources.into_iter().map(|(kind, image)| (kind, images.add(image))).collect();
+        let images = world.resource::<Assets<Image>>();
+        // One pixel of padding keeps neighbouring cells from bleeding in when sprites are scaled
+        let mut builder = TextureAtlasBuilder::default().padding(UVec2::ONE).max_size(Vec2::splat(2048.0));
+        for (_, handle) in handles.iter() {
+            builder.add_texture(Some(handle.id()), images.get(handle).expect("atlas source was just added"));
+        }
+        let (layout, atlas_image) = builder.finish().expect("sprite atlas sources fit in 2048x2048");
+
+        let indices = handles
+            .iter()
+            .map(|(kind, handle)| (*kind, layout.get_texture_index(handle).expect("every source is packed")))
+            .collect();
+        let mut images = world.resource_mut::<Assets<Image>>();
+        // The packed copy is all that's drawn from now on
+        for (_, handle) in handles.iter() {
+            images.remove(handle);
+        }
+        let image = images.add(atlas_image);
+        let layout = world.resource_mut::<Assets<TextureAtlasLayout>>().add(layout);
+        SpriteAtlas { image, layout, indices }
+    }
+
+    pub fn sprite(&self, kind: SpriteKind) -> TextureAtlas {
+        TextureAtlas {
+            layout: self.layout.clone(),
+            index: self.indices[&kind],
+        }
+    }
+}
+
+fn image_from_rgba(width: u32, height: u32, data: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d { width, height, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+// Read straight from disk (mods folder first) since the atlas is packed before the asset server has loaded anything.
+// A missing or broken file becomes a coloured block, so a bare clone still shows what is what
+fn load_png(path: &str, placeholder: [u8; 4]) -> Image {
+    match mods::read_data(path).map_err(image::ImageError::IoError).and_then(|bytes| image::load_from_memory(&bytes)) {
+        Ok(decoded) => {
+            let rgba = decoded.to_rgba8();
+            image_from_rgba(rgba.width(), rgba.height(), rgba.into_raw())
+        }
+        Err(error) => {
+            warn!("Could not load {path} ({error}), drawing a placeholder instead");
+            placeholder_image(placeholder)
+        }
+    }
+}
+
+// Flat colour with a darker one-pixel outline, so neighbouring invaders don't merge into one slab
+fn placeholder_image(color: [u8; 4]) -> Image {
+    let [r, g, b, a] = color;
+    let mut data = Vec::with_capacity((PLACEHOLDER_SIZE * PLACEHOLDER_SIZE * 4) as usize);
+    for y in 0..PLACEHOLDER_SIZE {
+        for x in 0..PLACEHOLDER_SIZE {
+            let edge = x == 0 || y == 0 || x == PLACEHOLDER_SIZE - 1 || y == PLACEHOLDER_SIZE - 1;
+            if edge {
+                data.extend_from_slice(&[r / 2, g / 2, b / 2, a]);
+            } else {
+                data.extend_from_slice(&color);
+            }
+        }
+    }
+    image_from_rgba(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, data)
+}
+
+fn solid_image(width: u32, height: u32) -> Image {
+    image_from_rgba(width, height, vec![255; (width * height * 4) as usize])
+}
+
+// Antialiased on both edges so it still reads as a ring when stretched
+fn ring_image() -> Image {
+    let center = RING_SIZE as f32 / 2.0;
+    let mut data = Vec::with_capacity((RING_SIZE * RING_SIZE * 4) as usize);
+    for y in 0..RING_SIZE {
+        for x in 0..RING_SIZE {
+            let distance = Vec2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center).length();
+            let from_edge = (distance - (center - RING_THICKNESS / 2.0 - 1.0)).abs();
+            let alpha = (1.0 - (from_edge - RING_THICKNESS / 2.0)).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    image_from_rgba(RING_SIZE, RING_SIZE, data)
+}
+
+// A solid cell with chunks bitten out of it
+fn damaged_bunker_image() -> Image {
+    let mut data = Vec::with_capacity((BUNKER_CELL_SIZE * BUNKER_CELL_SIZE * 4) as usize);
+    for y in 0..BUNKER_CELL_SIZE {
+        for x in 0..BUNKER_CELL_SIZE {
+            let bitten = (x * 3 + y * 5) % 7 < 2 || (x >= 5 && y <= 2);
+            data.extend_from_slice(&[255, 255, 255, if bitten { 0 } else { 255 }]);
+        }
+    }
+    image_from_rgba(BUNKER_CELL_SIZE, BUNKER_CELL_SIZE, data)
+}
+
+// A fireball that swells from yellow to red while its centre burns out
+fn explosion_frame(frame: usize) -> Image {
+    let progress = (frame + 1) as f32 / EXPLOSION_FRAMES as f32;
+    let center = EXPLOSION_FRAME_SIZE as f32 / 2.0;
+    let outer = center * progress;
+    let inner = outer * 0.8 * (frame as f32 / EXPLOSION_FRAMES as f32);
+    let mut data = Vec::with_capacity((EXPLOSION_FRAME_SIZE * EXPLOSION_FRAME_SIZE * 4) as usize);
+    for y in 0..EXPLOSION_FRAME_SIZE {
+        for x in 0..EXPLOSION_FRAME_SIZE {
+            let distance = Vec2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center).length();
+            if distance > outer || distance < inner {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+                continue;
+            }
+            let green = (220.0 * (1.0 - progress) + 40.0) as u8;
+            let alpha = (255.0 * (1.0 - progress * 0.6)) as u8;
+            data.extend_from_slice(&[255, green, 30, alpha]);
+        }
+    }
+    image_from_rgba(EXPLOSION_FRAME_SIZE, EXPLOSION_FRAME_SIZE, data)
+}
+
+pub fn spawn_explosion(commands: &mut Commands, assets: &GameAssets, position: Vec3) {
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(SpriteKind::Explosion(0)),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(48.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        Explosion {
+            frame: 0,
+            timer: Timer::from_seconds(EXPLOSION_FRAME_SECONDS, TimerMode::Repeating),
+        },
+        WaveScoped,
+    ));
+}
+
+fn animate_explosions(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<GameAssets>,
+    mut query: Query<(Entity, &mut Explosion, &mut TextureAtlas)>,
+) {
+    for (entity, mut explosion, mut atlas) in query.iter_mut() {
+        if !explosion.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        explosion.frame += 1;
+        if explosion.frame >= EXPLOSION_FRAMES {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        *atlas = assets.atlas.sprite(SpriteKind::Explosion(explosion.frame));
+    }
+}
+
+// Bullets, power-ups and scrap are spawned as plain coloured quads; pointing them at the atlas's white cell
+// keeps their look and lets them batch with everything else
+fn tint_plain_sprites(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    query: Query<Entity, (Or<(Added<Bullet>, Added<EnemyBullet>, Added<PowerUp>, Added<Scrap>)>, Without<TextureAtlas>)>,
+) {
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .insert((assets.atlas.image.clone(), assets.atlas.sprite(SpriteKind::Tint)));
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::input::{ActionState, PlayerAction};
+use super::{
+    on_wave_cleared, set_gravity_flip, spawn_power_up, storage, GameMode, GameState, GameplaySet, GravityFlip, Level, Palette,
+    PlayerName, PowerUp, QuitDialog, RestartRun, Ruleset, RunFinished, Score, WaveCleared, WaveWon, BOSS_LEVEL_INTERVAL,
+};
+
+// Every boss fight back to back (B on the main menu). The run jumps from boss level to boss level,
+// lives carry over from fight to fight, and each new fight opens with a couple of falling heal pickups.
+// Results go to their own leaderboard, shared by every profile on the machine.
+pub const FIGHTS: u32 = 4;
+const BOSS_RUSH_SCORES_FILE: &str = "boss_rush.ron";
+const MAX_BOSS_RUSH_SCORES: usize = 10;
+const HEAL_PICKUP_X: [f32; 2] = [-200.0, 200.0];
+const HEAL_PICKUP_Y: f32 = 300.0;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BossRushEntry {
+    pub name: String,
+    pub bosses: u32,
+    pub score: u32,
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct BossRushScores(pub Vec<BossRushEntry>);
+
+#[derive(Resource, Default)]
+struct BossRushRun {
+    bosses_defeated: u32,
+    // The ruleset picked before the rush, which needs bosses switched on
+    stashed_ruleset: Option<Ruleset>,
+}
+
+pub struct BossRushPlugin;
+
+impl Plugin for BossRushPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BossRushScores::load())
+            .init_resource::<BossRushRun>()
+            .add_systems(Update, start_boss_rush.run_if(in_state(GameState::MainMenu)).in_set(GameplaySet::Input))
+            .add_systems(
+                Update,
+                (
+                    leave_finished_rush.in_set(GameplaySet::Input),
+                    (reset_boss_rush, record_boss_defeats.after(on_wave_cleared), record_lost_rush)
+                        .chain()
+                        .in_set(GameplaySet::Collision),
+                )
+                    .run_if(boss_rush_mode),
+            )
+            .add_systems(OnEnter(GameState::LevelIntro), drop_heal_pickups.after(set_gravity_flip).run_if(boss_rush_mode))
+            .add_systems(OnEnter(GameState::MainMenu), end_boss_rush);
+    }
+}
+
+pub fn boss_rush_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::BossRush
+}
+
+// Boss fights sit on every BOSS_LEVEL_INTERVAL-th level
+pub fn first_level() -> u32 {
+    BOSS_LEVEL_INTERVAL
+}
+
+pub fn last_level() -> u32 {
+    BOSS_LEVEL_INTERVAL * FIGHTS
+}
+
+impl BossRushScores {
+    fn load() -> Self {
+        storage::load_ron(&storage::data_file(BOSS_RUSH_SCORES_FILE))
+    }
+
+    fn save(&self) {
+        storage::save_ron(&storage::data_file(BOSS_RUSH_SCORES_FILE), self, "boss rush scores");
+    }
+
+    // More bosses beaten ranks first, score breaks ties
+    fn insert(&mut self, entry: BossRushEntry) {
+        self.0.push(entry);
+        self.0.sort_by(|a, b| b.bosses.cmp(&a.bosses).then(b.score.cmp(&a.score)));
+        self.0.truncate(MAX_BOSS_RUSH_SCORES);
+        self.save();
+    }
+}
+
+fn start_boss_rush(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    quit_dialog: Res<QuitDialog>,
+    mut mode: ResMut<GameMode>,
+    mut ruleset: ResMut<Ruleset>,
+    mut run: ResMut<BossRushRun>,
+    mut restart_events: EventWriter<RestartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyB) || quit_dialog.open || *mode != GameMode::Arcade {
+        return;
+    }
+    run.stashed_ruleset = Some(*ruleset);
+    *ruleset = Ruleset::Modern;
+    *mode = GameMode::BossRush;
+    println!("Boss rush: {} fights", FIGHTS);
+    restart_events.send(RestartRun);
+    next_state.set(GameState::LevelIntro);
+}
+
+fn reset_boss_rush(mut restart_events: EventReader<RestartRun>, mut run: ResMut<BossRushRun>) {
+    if restart_events.read().count() > 0 {
+        run.bosses_defeated = 0;
+    }
+}
+
+fn record_boss_defeats(
+    mut cleared_events: EventReader<WaveCleared>,
+    level: Res<Level>,
+    score: Res<Score>,
+    player_name: Res<PlayerName>,
+    mut run: ResMut<BossRushRun>,
+    mut scores: ResMut<BossRushScores>,
+) {
+    // One defeat per boss level, however many clear events arrive
+    let fight = level.0 / BOSS_LEVEL_INTERVAL;
+    if cleared_events.read().count() == 0 || run.bosses_defeated >= fight {
+        return;
+    }
+    run.bosses_defeated = fight;
+    println!("Boss {} of {} down", fight, FIGHTS);
+    if fight >= FIGHTS {
+        scores.insert(BossRushEntry {
+            name: player_name.0.clone(),
+            bosses: run.bosses_defeated,
+            score: score.0,
+        });
+    }
+}
+
+fn record_lost_rush(
+    mut finished_events: EventReader<RunFinished>,
+    player_name: Res<PlayerName>,
+    run: Res<BossRushRun>,
+    mut scores: ResMut<BossRushScores>,
+) {
+    for finished in finished_events.read() {
+        scores.insert(BossRushEntry {
+            name: player_name.0.clone(),
+            bosses: run.bosses_defeated,
+            score: finished.score,
+        });
+    }
+}
+
+// Falls from the boss's side as the next fight opens; whatever isn't caught is gone
+fn drop_heal_pickups(mut commands: Commands, palette: Res<Palette>, level: Res<Level>, flip: Res<GravityFlip>) {
+    if level.0 <= first_level() {
+        return;
+    }
+    for x in HEAL_PICKUP_X {
+        spawn_power_up(&mut commands, &palette, Vec3::new(x, flip.y(HEAL_PICKUP_Y), 0.0), PowerUp::Heal);
+    }
+}
+
+// Next level is off after the last boss: R runs the rush again, Enter goes back to the menu
+fn leave_finished_rush(
+    action_state: Res<ActionState>,
+    wave_won: Res<WaveWon>,
+    level: Res<Level>,
+    mut restart_events: EventWriter<RestartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !wave_won.0 || level.0 < last_level() {
+        return;
+    }
+    if action_state.just_pressed(PlayerAction::Restart) {
+        restart_events.send(RestartRun);
+    } else if action_state.just_pressed(PlayerAction::Confirm) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn end_boss_rush(mut run: ResMut<BossRushRun>, mut mode: ResMut<GameMode>, mut ruleset: ResMut<Ruleset>) {
+    if let Some(stashed) = run.stashed_ruleset.take() {
+        *ruleset = stashed;
+        *mode = GameMode::Arcade;
+    }
+}
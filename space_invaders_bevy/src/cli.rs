@@ -0,0 +1,60 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use super::Difficulty;
+
+// Command-line options, parsed once before the app is built
+#[derive(Parser)]
+#[command(version, about = "Space Invaders built with Bevy")]
+pub struct Cli {
+    /// Level to start on, skipping the main menu
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub level: Option<u32>,
+    /// Seed for gameplay randomness, so a run can be reproduced
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Enemy speed and fire rate
+    #[arg(long, value_enum, default_value_t = Difficulty::Normal)]
+    pub difficulty: Difficulty,
+    /// Window size, e.g. 1280x720
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_window_size)]
+    pub windowed: Option<(f32, f32)>,
+    /// Run the simulation without opening a window or using the GPU
+    #[arg(long)]
+    pub headless: bool,
+    /// Player two commands the invaders
+    #[arg(long)]
+    pub versus: bool,
+    /// Endless survival: one wave that never ends and keeps getting harder
+    #[arg(long, conflicts_with = "versus")]
+    pub survival: bool,
+    /// Clear the first five waves as fast as possible
+    #[arg(long, conflicts_with_all = ["versus", "survival"])]
+    pub time_attack: bool,
+    /// Host a LAN co-op game on this UDP port
+    #[arg(long, value_name = "PORT", conflicts_with = "join")]
+    pub host: Option<u16>,
+    /// Join a LAN co-op game at HOST:PORT
+    #[arg(long, value_name = "ADDRESS")]
+    pub join: Option<SocketAddr>,
+    /// Write a JSON log of every finished run into this folder
+    #[arg(long, value_name = "DIR")]
+    pub telemetry: Option<PathBuf>,
+    /// The ship wraps around the screen edges instead of stopping at them
+    #[arg(long)]
+    pub wrap: bool,
+}
+
+fn parse_window_size(value: &str) -> Result<(f32, f32), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got '{value}'"))?;
+    let width: u32 = width.parse().map_err(|_| format!("invalid width '{width}'"))?;
+    let height: u32 = height.parse().map_err(|_| format!("invalid height '{height}'"))?;
+    if width == 0 || height == 0 {
+        return Err("window size must be non-zero".to_string());
+    }
+    Ok((width as f32, height as f32))
+}
@@ -0,0 +1,264 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::profiles::CurrentProfile;
+use super::{
+    record_high_score, storage, DailyText, Difficulty, GameMode, GameRng, GameState, GameplaySet, Locale, PlayerName, PlayfieldBounds,
+    QuitDialog, RestartRun, Ruleset, RunFinished,
+};
+
+// One run a day that is the same for everybody: the seed and the modifiers come from the UTC date.
+// Each profile gets a single attempt per day (tracked in its daily.ron), and scores go to a daily
+// leaderboard shared by every profile on the machine.
+const DAILY_SCORES_FILE: &str = "daily_scores.ron";
+const DAILY_ATTEMPT_FILE: &str = "daily.ron";
+const SECONDS_PER_DAY: u64 = 86_400;
+// Older days are dropped from the leaderboard file on save
+const DAILY_HISTORY_DAYS: u64 = 30;
+
+#[derive(Clone, Copy)]
+pub struct DailyModifiers {
+    pub difficulty: Difficulty,
+    pub ruleset: Ruleset,
+    pub wrap: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyScore {
+    pub day: u64,
+    pub name: String,
+    pub score: u32,
+    pub level: u32,
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct DailyScores(Vec<DailyScore>);
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct DailyAttempt {
+    last_day: Option<u64>,
+}
+
+// What the modifiers replaced, put back once the daily run is over
+struct DailyStash {
+    difficulty: Difficulty,
+    ruleset: Ruleset,
+    wrap: bool,
+}
+
+#[derive(Resource, Default)]
+struct ActiveDaily(Option<DailyStash>);
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        let attempt = DailyAttempt::load(app.world.resource::<CurrentProfile>());
+        app.insert_resource(attempt)
+            .insert_resource(DailyScores::load())
+            .init_resource::<ActiveDaily>()
+            .add_systems(
+                Update,
+                (reload_daily_attempt, start_daily_challenge.run_if(in_state(GameState::MainMenu)))
+                    .chain()
+                    .in_set(GameplaySet::Input),
+            )
+            .add_systems(Update, (finish_daily_challenge.after(record_high_score), update_daily_text).in_set(GameplaySet::Ui))
+            .add_systems(OnEnter(GameState::MainMenu), abandon_daily_challenge);
+    }
+}
+
+pub fn today() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs() / SECONDS_PER_DAY).unwrap_or(0)
+}
+
+// Days since 1970-01-01 as YYYY-MM-DD (Howard Hinnant's civil_from_days)
+pub fn date_label(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+fn seed_for_day(day: u64) -> u64 {
+    day.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xDA11_C4A1_1E96_E000
+}
+
+impl DailyModifiers {
+    pub fn for_day(day: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed_for_day(day) ^ 1);
+        DailyModifiers {
+            difficulty: if rng.random_bool(0.4) { Difficulty::Hard } else { Difficulty::Normal },
+            ruleset: if rng.random_bool(0.3) { Ruleset::Classic } else { Ruleset::Modern },
+            wrap: rng.random_bool(0.25),
+        }
+    }
+}
+
+impl DailyScores {
+    fn load() -> Self {
+        storage::load_ron(&storage::data_file(DAILY_SCORES_FILE))
+    }
+
+    fn save(&self) {
+        storage::save_ron(&storage::data_file(DAILY_SCORES_FILE), self, "daily scores");
+    }
+
+    fn insert(&mut self, entry: DailyScore) {
+        let oldest = entry.day.saturating_sub(DAILY_HISTORY_DAYS);
+        self.0.retain(|score| score.day >= oldest);
+        self.0.push(entry);
+        self.0.sort_by(|a, b| b.day.cmp(&a.day).then(b.score.cmp(&a.score)));
+    }
+
+    // Best first
+    pub fn for_day(&self, day: u64) -> impl Iterator<Item = &DailyScore> {
+        self.0.iter().filter(move |score| score.day == day)
+    }
+}
+
+impl DailyAttempt {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(DAILY_ATTEMPT_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(DAILY_ATTEMPT_FILE), self, "the daily attempt");
+    }
+
+    fn played(&self, day: u64) -> bool {
+        self.last_day == Some(day)
+    }
+}
+
+fn reload_daily_attempt(profile: Res<CurrentProfile>, mut attempt: ResMut<DailyAttempt>) {
+    if profile.is_changed() && !profile.is_added() {
+        *attempt = DailyAttempt::load(&profile);
+    }
+}
+
+// Tab on the main menu. The attempt is spent as soon as the run starts, so quitting doesn't buy a retry
+fn start_daily_challenge(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    quit_dialog: Res<QuitDialog>,
+    profile: Res<CurrentProfile>,
+    mut attempt: ResMut<DailyAttempt>,
+    mut active: ResMut<ActiveDaily>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut ruleset: ResMut<Ruleset>,
+    mut bounds: ResMut<PlayfieldBounds>,
+    mut rng: ResMut<GameRng>,
+    mut restart_events: EventWriter<RestartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) || quit_dialog.open || *mode != GameMode::Arcade {
+        return;
+    }
+    let day = today();
+    if attempt.played(day) {
+        println!("Today's daily challenge has already been played");
+        return;
+    }
+    attempt.last_day = Some(day);
+    attempt.save(&profile);
+
+    active.0 = Some(DailyStash {
+        difficulty: *difficulty,
+        ruleset: *ruleset,
+        wrap: bounds.wrap,
+    });
+    let modifiers = DailyModifiers::for_day(day);
+    *difficulty = modifiers.difficulty;
+    *ruleset = modifiers.ruleset;
+    bounds.wrap = modifiers.wrap;
+    *rng = GameRng::seeded(seed_for_day(day));
+    *mode = GameMode::Daily;
+    println!("Daily challenge {}: {:?} rules, wrap {}", date_label(day), modifiers.ruleset, modifiers.wrap);
+    restart_events.send(RestartRun);
+    next_state.set(GameState::LevelIntro);
+}
+
+fn finish_daily_challenge(
+    mut finished_events: EventReader<RunFinished>,
+    mut active: ResMut<ActiveDaily>,
+    mut scores: ResMut<DailyScores>,
+    player_name: Res<PlayerName>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut ruleset: ResMut<Ruleset>,
+    mut bounds: ResMut<PlayfieldBounds>,
+) {
+    for run in finished_events.read() {
+        let Some(stash) = active.0.take() else {
+            continue;
+        };
+        scores.insert(DailyScore {
+            day: today(),
+            name: player_name.0.clone(),
+            score: run.score,
+            level: run.level,
+        });
+        scores.save();
+        restore(stash, &mut mode, &mut difficulty, &mut ruleset, &mut bounds);
+    }
+}
+
+// Every way back to the menu passes a finished run, but the modifiers must never outlive the daily run
+fn abandon_daily_challenge(
+    mut active: ResMut<ActiveDaily>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut ruleset: ResMut<Ruleset>,
+    mut bounds: ResMut<PlayfieldBounds>,
+) {
+    if let Some(stash) = active.0.take() {
+        restore(stash, &mut mode, &mut difficulty, &mut ruleset, &mut bounds);
+    }
+}
+
+fn restore(
+    stash: DailyStash,
+    mode: &mut GameMode,
+    difficulty: &mut Difficulty,
+    ruleset: &mut Ruleset,
+    bounds: &mut PlayfieldBounds,
+) {
+    *mode = GameMode::Arcade;
+    *difficulty = stash.difficulty;
+    *ruleset = stash.ruleset;
+    bounds.wrap = stash.wrap;
+}
+
+fn update_daily_text(
+    attempt: Res<DailyAttempt>,
+    scores: Res<DailyScores>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, Ref<DailyText>)>,
+) {
+    for (mut text, marker) in query.iter_mut() {
+        if !attempt.is_changed() && !scores.is_changed() && !locale.is_changed() && !marker.is_added() {
+            continue;
+        }
+        let day = today();
+        let modifiers = DailyModifiers::for_day(day);
+        let mut parts = vec![date_label(day), locale.get(modifiers.difficulty.label_key()).to_string(), locale.get(modifiers.ruleset.label_key()).to_string()];
+        if modifiers.wrap {
+            parts.push(locale.get("daily.wrap").to_string());
+        }
+        if attempt.played(day) {
+            parts.push(locale.get("daily.played").to_string());
+        }
+        text.sections[1].value = parts.join(" / ");
+    }
+}
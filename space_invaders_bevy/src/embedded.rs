@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+
+use bevy::asset::io::{AssetReader, AssetReaderError, PathStream, Reader, VecReader};
+use bevy::utils::BoxedFuture;
+
+// The whole assets folder compiled into the executable, so a release can ship as one file.
+// It sits underneath the assets folder and mods/assets: anything found on disk still wins
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_bytes!(concat!("../assets/", $path)))),*]
+    };
+}
+
+static FILES: &[(&str, &[u8])] = embed![
+    "config.toml",
+    "player.png",
+    "enemy2.png",
+    "fonts/FiraSans-Bold.ttf",
+    "i18n/en.ron",
+    "i18n/pt.ron",
+    "i18n/es.ron",
+    "levels/formation.level.ron",
+    "scripts/elite.rhai",
+    "shaders/crt.wgsl",
+    "sounds/clink.wav",
+    "sounds/explosion.wav",
+];
+
+pub fn get(relative: &str) -> Option<&'static [u8]> {
+    let relative = relative.replace('\\', "/");
+    FILES.iter().find(|(path, _)| *path == relative).map(|(_, bytes)| *bytes)
+}
+
+pub struct EmbeddedAssetReader;
+
+impl AssetReader for EmbeddedAssetReader {
+    fn read<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            match get(&path.to_string_lossy()) {
+                Some(bytes) => Ok(Box::new(VecReader::new(bytes.to_vec())) as Box<Reader<'a>>),
+                None => Err(AssetReaderError::NotFound(path.to_path_buf())),
+            }
+        })
+    }
+
+    // No .meta files are bundled; loaders fall back to their defaults
+    fn read_meta<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(PathBuf::from(path))) })
+    }
+
+    fn read_directory<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(PathBuf::from(path))) })
+    }
+
+    fn is_directory<'a>(&'a self, _path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move { Ok(false) })
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use super::{
+    atlas, check_game_over, game_over, net, set_gravity_flip, Damaged, EnemyBullet, FlashOnHit, Friendly, FriendlyLost, GameAssets,
+    GameState, GameplaySet, GravityFlip, Health, Level, LevelLayout, Locale, Ruleset, WaveScoped,
+};
+
+// Escort levels (the `escort` rule in the level file): a slow cargo ship patrols below the player
+// for the whole wave. Enemy bullets that get past the player can hit it, and losing it ends the run
+// just like losing the last life, so clearing the sky is no longer the only job.
+const CARGO_SPEED: f32 = 40.0;
+const CARGO_Y: f32 = -255.0;
+const CARGO_SIZE: Vec2 = Vec2::new(70.0, 18.0);
+
+#[derive(Component)]
+struct CargoShip {
+    direction: f32,
+}
+
+// Set when an escort wave starts; `lost` keeps the HUD line up after the ship is gone
+#[derive(Resource, Default)]
+struct Escort {
+    active: bool,
+    max_health: u32,
+    lost: bool,
+}
+
+#[derive(Component)]
+struct EscortText;
+
+pub struct EscortPlugin;
+
+impl Plugin for EscortPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Escort>()
+            .add_systems(Startup, setup_escort_ui)
+            .add_systems(
+                OnEnter(GameState::LevelIntro),
+                spawn_cargo_ship.after(set_gravity_flip).run_if(not(net::is_client)),
+            )
+            .add_systems(
+                Update,
+                cargo_movement
+                    .in_set(GameplaySet::Movement)
+                    .run_if(not(game_over))
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                enemy_bullet_friendly_collision
+                    .before(check_game_over)
+                    .in_set(GameplaySet::Collision)
+                    .run_if(not(game_over))
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::MainMenu), reset_escort)
+            .add_systems(Update, update_escort_text.in_set(GameplaySet::Ui));
+    }
+}
+
+fn reset_escort(mut escort: ResMut<Escort>) {
+    *escort = Escort::default();
+}
+
+fn spawn_cargo_ship(
+    mut commands: Commands,
+    layout: Res<LevelLayout>,
+    level: Res<Level>,
+    ruleset: Res<Ruleset>,
+    flip: Res<GravityFlip>,
+    windows: Query<&Window>,
+    mut escort: ResMut<Escort>,
+) {
+    *escort = Escort::default();
+    let Some(health) = layout.escort_health(*ruleset, level.0) else {
+        return;
+    };
+    // Enters from the left edge and turns around at each side
+    let start_x = windows.get_single().map(|window| -window.width() / 2.0).unwrap_or(-400.0);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.55, 0.8, 0.6),
+                custom_size: Some(CARGO_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(start_x, flip.y(CARGO_Y), -0.1),
+            ..default()
+        },
+        CargoShip { direction: 1.0 },
+        Friendly,
+        Health(health),
+        FlashOnHit { tint: Color::rgb(2.5, 0.4, 0.4) },
+        WaveScoped,
+    ));
+    escort.active = true;
+    escort.max_health = health;
+    println!("Escort level: protect the cargo ship");
+}
+
+fn cargo_movement(time: Res<Time>, windows: Query<&Window>, mut query: Query<(&mut Transform, &mut CargoShip)>) {
+    let half_width = windows.get_single().map(|window| window.width() / 2.0).unwrap_or(400.0) - CARGO_SIZE.x / 2.0;
+    for (mut transform, mut cargo) in query.iter_mut() {
+        transform.translation.x += cargo.direction * CARGO_SPEED * time.delta_seconds();
+        if transform.translation.x > half_width {
+            cargo.direction = -1.0;
+        } else if transform.translation.x < -half_width && cargo.direction < 0.0 {
+            cargo.direction = 1.0;
+        }
+    }
+}
+
+fn enemy_bullet_friendly_collision(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    bullet_query: Query<(Entity, &Transform), With<EnemyBullet>>,
+    mut friendly_query: Query<(Entity, &Transform, &Sprite, &mut Health), With<Friendly>>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut lost_events: EventWriter<FriendlyLost>,
+    mut escort: ResMut<Escort>,
+) {
+    for (friendly, friendly_tf, friendly_sprite, mut health) in friendly_query.iter_mut() {
+        let half_size = friendly_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        for (bullet_entity, bullet_tf) in bullet_query.iter() {
+            let distance = (bullet_tf.translation - friendly_tf.translation).truncate().abs();
+            if distance.x > half_size.x || distance.y > half_size.y {
+                continue;
+            }
+            commands.entity(bullet_entity).despawn();
+            health.0 = health.0.saturating_sub(1);
+            if health.0 > 0 {
+                damaged_events.send(Damaged { entity: friendly });
+                println!("Cargo ship hit! {} left", health.0);
+                continue;
+            }
+            commands.entity(friendly).despawn_recursive();
+            atlas::spawn_explosion(&mut commands, &assets, friendly_tf.translation);
+            lost_events.send(FriendlyLost);
+            escort.lost = true;
+            break;
+        }
+    }
+}
+
+fn setup_escort_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::rgb(0.55, 0.8, 0.6),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(220.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        EscortText,
+    ));
+}
+
+fn update_escort_text(
+    escort: Res<Escort>,
+    locale: Res<Locale>,
+    cargo_query: Query<&Health, With<CargoShip>>,
+    mut query: Query<&mut Text, With<EscortText>>,
+) {
+    let label = if escort.lost {
+        locale.get("escort.lost").to_string()
+    } else if let (true, Ok(health)) = (escort.active, cargo_query.get_single()) {
+        format!("{}{}/{}", locale.get("hud.cargo"), health.0, escort.max_health)
+    } else {
+        String::new()
+    };
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
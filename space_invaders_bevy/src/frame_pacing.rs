@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
+
+use super::{GameOver, GameState, Settings, SettingsMenu};
+
+// Static screens (menus, pause, game over) only redraw on input or a few times a second,
+// and the frame cap setting sleeps off whatever is left of each frame's budget
+const LOW_POWER_FPS: f32 = 15.0;
+
+pub struct FramePacingPlugin;
+
+impl Plugin for FramePacingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_power_mode).add_systems(Last, limit_frame_rate);
+    }
+}
+
+fn update_power_mode(
+    // Missing in headless runs, which have no winit event loop
+    winit_settings: Option<ResMut<WinitSettings>>,
+    state: Res<State<GameState>>,
+    settings_menu: Res<SettingsMenu>,
+    game_over: Res<GameOver>,
+    mut was_idle: Local<Option<bool>>,
+) {
+    let Some(mut winit_settings) = winit_settings else {
+        return;
+    };
+    let idle = matches!(state.get(), GameState::MainMenu | GameState::Paused) || settings_menu.open || game_over.0;
+    // Only write on a real change so change detection doesn't fire every frame
+    if *was_idle == Some(idle) {
+        return;
+    }
+    *was_idle = Some(idle);
+    let mode = if idle {
+        UpdateMode::Reactive { wait: Duration::from_secs_f32(1.0 / LOW_POWER_FPS) }
+    } else {
+        UpdateMode::Continuous
+    };
+    winit_settings.focused_mode = mode;
+    winit_settings.unfocused_mode = mode;
+}
+
+fn limit_frame_rate(settings: Res<Settings>, mut last_frame: Local<Option<Instant>>) {
+    if let (Some(fps), Some(last_frame)) = (settings.frame_cap.fps(), *last_frame) {
+        let budget = Duration::from_secs_f32(1.0 / fps);
+        let elapsed = last_frame.elapsed();
+        if elapsed < budget {
+            std::thread::sleep(budget - elapsed);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use super::{swallow_console_input, ControlScheme, Settings};
+
+// Gameplay reads actions instead of raw devices. Every frame the bindings table is checked against
+// the keyboard, gamepads, mouse and touch screen, and the result is stored in ActionState.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PlayerAction {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    NextLevel,
+    Restart,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    Bomb,
+    Pause,
+    // Picks up the saved run from the main menu
+    Continue,
+    // New Game+ perk choice on the final level's win screen
+    PerkOne,
+    PerkTwo,
+    PerkThree,
+    // Spends scrap on a spare life between waves
+    BuyLife,
+    // The second player's controls in versus mode
+    VersusLeft,
+    VersusRight,
+    VersusFire,
+    VersusDive,
+}
+
+// Thirds of the screen, for touch input
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TouchZone {
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+    // Only live in the mouse control scheme, so focusing the window with a click doesn't fire
+    Mouse(MouseButton),
+    Touch(TouchZone),
+}
+
+#[derive(Resource)]
+pub struct InputMap {
+    pub bindings: Vec<(PlayerAction, InputBinding)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputBinding::*;
+        use PlayerAction::*;
+        // Key codes are physical positions, so KeyA/KeyD/KeyW sit under the same fingers on
+        // AZERTY (Q/D/Z) and other layouts. W and Up double as fire for laptops without a comfy space bar.
+        InputMap {
+            bindings: vec![
+                (MoveLeft, Key(KeyCode::ArrowLeft)),
+                (MoveLeft, Key(KeyCode::KeyA)),
+                (MoveLeft, Gamepad(GamepadButtonType::DPadLeft)),
+                (MoveLeft, Touch(TouchZone::Left)),
+                (MoveRight, Key(KeyCode::ArrowRight)),
+                (MoveRight, Key(KeyCode::KeyD)),
+                (MoveRight, Gamepad(GamepadButtonType::DPadRight)),
+                (MoveRight, Touch(TouchZone::Right)),
+                (Fire, Key(KeyCode::Space)),
+                (Fire, Key(KeyCode::KeyW)),
+                (Fire, Key(KeyCode::ArrowUp)),
+                (Fire, Gamepad(GamepadButtonType::South)),
+                (Fire, Mouse(MouseButton::Left)),
+                (Fire, Touch(TouchZone::Middle)),
+                (NextLevel, Key(KeyCode::KeyN)),
+                (NextLevel, Gamepad(GamepadButtonType::North)),
+                (Restart, Key(KeyCode::KeyR)),
+                (MenuUp, Key(KeyCode::ArrowUp)),
+                (MenuUp, Key(KeyCode::KeyW)),
+                (MenuUp, Gamepad(GamepadButtonType::DPadUp)),
+                (MenuDown, Key(KeyCode::ArrowDown)),
+                (MenuDown, Key(KeyCode::KeyS)),
+                (MenuDown, Gamepad(GamepadButtonType::DPadDown)),
+                (Confirm, Key(KeyCode::Enter)),
+                (Confirm, Key(KeyCode::Space)),
+                (Confirm, Gamepad(GamepadButtonType::South)),
+                (Bomb, Key(KeyCode::KeyB)),
+                (Bomb, Gamepad(GamepadButtonType::West)),
+                (Pause, Key(KeyCode::KeyP)),
+                (Pause, Gamepad(GamepadButtonType::Start)),
+                (Continue, Key(KeyCode::KeyC)),
+                (Continue, Gamepad(GamepadButtonType::Select)),
+                (PerkOne, Key(KeyCode::Digit1)),
+                (PerkOne, Gamepad(GamepadButtonType::West)),
+                (PerkTwo, Key(KeyCode::Digit2)),
+                (PerkTwo, Gamepad(GamepadButtonType::North)),
+                (PerkThree, Key(KeyCode::Digit3)),
+                (PerkThree, Gamepad(GamepadButtonType::East)),
+                (BuyLife, Key(KeyCode::KeyU)),
+                (BuyLife, Gamepad(GamepadButtonType::RightTrigger)),
+                // The pad belongs to the ship, so the invaders' side stays on the keyboard
+                (VersusLeft, Key(KeyCode::KeyJ)),
+                (VersusRight, Key(KeyCode::KeyL)),
+                (VersusFire, Key(KeyCode::KeyI)),
+                (VersusDive, Key(KeyCode::KeyK)),
+            ],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<PlayerAction>,
+    just_pressed: HashSet<PlayerAction>,
+    just_released: HashSet<PlayerAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: PlayerAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: PlayerAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: PlayerAction) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem).after(swallow_console_input));
+    }
+}
+
+pub fn update_action_state(
+    input_map: Res<InputMap>,
+    settings: Res<Settings>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Query<&Window>,
+    mut action_state: ResMut<ActionState>,
+) {
+    let width = windows.get_single().map(|window| window.width()).unwrap_or(1.0);
+    let zone_of = |x: f32| {
+        if x < width / 3.0 {
+            TouchZone::Left
+        } else if x > width * 2.0 / 3.0 {
+            TouchZone::Right
+        } else {
+            TouchZone::Middle
+        }
+    };
+    let binding_pressed = |binding: &InputBinding| match *binding {
+        InputBinding::Key(key) => keyboard_input.pressed(key),
+        InputBinding::Gamepad(button_type) => gamepad_input.get_pressed().any(|button| button.button_type == button_type),
+        InputBinding::Mouse(button) => settings.controls == ControlScheme::Mouse && mouse_input.pressed(button),
+        InputBinding::Touch(zone) => touches.iter().any(|touch| zone_of(touch.position().x) == zone),
+    };
+
+    let pressed: HashSet<PlayerAction> = input_map
+        .bindings
+        .iter()
+        .filter(|(_, binding)| binding_pressed(binding))
+        .map(|(action, _)| *action)
+        .collect();
+    // Derived from the previous frame, so an action held through two bindings only starts and stops once
+    let just_pressed = pressed.difference(&action_state.pressed).copied().collect();
+    let just_released = action_state.pressed.difference(&pressed).copied().collect();
+    *action_state = ActionState { pressed, just_pressed, just_released };
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+// Blocking HTTP calls against the online leaderboard; callers run these on the IO task pool
+const TOP_SCORES: usize = 20;
+
+#[derive(Serialize)]
+pub struct Submission<'a> {
+    pub name: &'a str,
+    pub score: u32,
+    pub mode: &'a str,
+    pub reduced_speed: bool,
+    pub version: &'a str,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OnlineScore {
+    pub name: String,
+    pub score: u32,
+}
+
+pub fn submit_score(url: &str, submission: &Submission) -> Result<(), String> {
+    ureq::post(url)
+        .send_json(submission)
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+pub fn fetch_top_scores(url: &str) -> Result<Vec<OnlineScore>, String> {
+    let mut scores: Vec<OnlineScore> = ureq::get(url)
+        .query("limit", &TOP_SCORES.to_string())
+        .call()
+        .map_err(|error| error.to_string())?
+        .into_json()
+        .map_err(|error| error.to_string())?;
+    scores.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+    scores.truncate(TOP_SCORES);
+    Ok(scores)
+}
@@ -0,0 +1,6115 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod achievements;
+mod atlas;
+mod boss_rush;
+#[cfg(feature = "crt")]
+mod crt;
+mod daily;
+#[cfg(feature = "embed-assets")]
+mod embedded;
+mod escort;
+mod frame_pacing;
+mod input;
+#[cfg(feature = "online-leaderboard")]
+mod leaderboard;
+mod loaders;
+mod loading;
+pub mod mods;
+mod net;
+mod new_game_plus;
+mod player_stats;
+mod profiles;
+mod random_events;
+mod rumble;
+mod scrap;
+mod screenshots;
+mod sounds;
+mod storage;
+mod survival;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod telemetry;
+mod time_attack;
+
+use std::collections::{BTreeMap, HashMap};
+use std::f32::consts::PI;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::input::ButtonInput;
+use bevy::window::{WindowCloseRequested, WindowFocused};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use input::{ActionState, PlayerAction};
+use profiles::CurrentProfile;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+// === CONSTANTS ===
+const SPREAD_ANGLE: f32 = PI / 12.0;
+// Hard turns some straight shots into aimed ones, off by up to this many pixels
+const AIM_ERROR: f32 = 40.0;
+const BURST_GAP: f32 = 30.0;
+const WIGGLE_AMPLITUDE: f32 = 12.0;
+const WIGGLE_FREQUENCY: f32 = 10.0;
+const ZIGZAG_SPEED: f32 = 140.0;
+const ZIGZAG_PERIOD: f32 = 0.25;
+const HOMING_CHANCE: f64 = 0.2;
+const HOMING_TURN_RATE: f32 = 1.2;
+const HOMING_LIFETIME: f32 = 4.0;
+const DODGE_LOOKAHEAD: f32 = 0.4;
+const DODGE_COOLDOWN: f32 = 1.5;
+const DODGE_SPEED: f32 = 240.0;
+const STEREO_EAR_GAP: f32 = 2.0;
+const HIT_FLASH_DURATION: f32 = 0.08;
+const POWER_UP_FALL_SPEED: f32 = 120.0;
+const OFFSCREEN_MARGIN: f32 = 20.0;
+const MAX_WEAPON_TIER: u8 = 3;
+const LASER_CHANCE: f64 = 0.25;
+const LASER_DURATION: f32 = 5.0;
+const LASER_TICK: f32 = 0.2;
+const LASER_WIDTH: f32 = 8.0;
+const SPREAD_SHOT_CHANCE: f64 = 0.3;
+const SPREAD_SHOT_DURATION: f32 = 8.0;
+const PLAYER_SPREAD_ANGLE: f32 = PI / 12.0;
+const PIERCE_CHANCE: f64 = 0.3;
+const PIERCE_DURATION: f32 = 8.0;
+const PIERCE_KILLS: u8 = 3;
+const CHARGE_SHOT_DAMAGE: u32 = 3;
+const BOMB_DAMAGE: u32 = 3;
+const BOMB_FLASH_DURATION: f32 = 0.4;
+const SHIELD_RADIUS: f32 = 34.0;
+const REGEN_SHIELD_SECONDS: f32 = 5.0;
+const FIRE_BUFFER_SECONDS: f32 = 0.1;
+const HEAT_PER_SHOT: f32 = 0.12;
+const HEAT_PER_CHARGE_SHOT: f32 = 0.4;
+const HEAT_COOL_RATE: f32 = 0.35;
+const OVERHEAT_LOCKOUT: f32 = 2.0;
+const LEVELS_PER_THEME: u32 = 3;
+const STAR_LAYERS: [(usize, f32, f32); 3] = [(60, 20.0, 1.5), (35, 45.0, 2.0), (15, 90.0, 3.0)];
+const TRAIL_LIFETIME: f32 = 0.15;
+const ENEMY_KILL_SCORE: u32 = 100;
+// Clearing a wave instantly is worth the full bonus, shrinking to nothing at the window's end
+const TIME_BONUS_MAX: u32 = 1000;
+const TIME_BONUS_WINDOW: f32 = 30.0;
+const DEATH_DEBRIS: usize = 16;
+const DEATH_DEBRIS_SPEED: f32 = 220.0;
+const DEATH_DEBRIS_LIFETIME: f32 = 0.5;
+const DEATH_SLOW_MOTION_SECONDS: f32 = 1.0;
+const DEATH_TIME_SCALE: f32 = 0.25;
+const KILL_CAM_SECONDS: f32 = 0.7;
+const KILL_CAM_TIME_SCALE: f32 = 0.3;
+const KILL_CAM_ZOOM: f32 = 0.4;
+const SCORE_POPUP_LIFETIME: f32 = 0.5;
+const SCORE_POPUP_RISE: f32 = 60.0;
+const LEVEL_INTRO_SECONDS: f32 = 3.0;
+const MAX_HIGH_SCORES: usize = 10;
+const HIGH_SCORES_FILE: &str = "highscores.ron";
+const SAVE_FILE: &str = "savegame.ron";
+const SETTINGS_FILE: &str = "settings.ron";
+const VERSUS_FIRE_COOLDOWN: f32 = 0.6;
+const VERSUS_DIVE_COOLDOWN: f32 = 3.0;
+const DIVE_STEER_SPEED: f32 = 160.0;
+const DIVE_SWING: f32 = 180.0;
+const DIVE_SHOOT_COOLDOWN: f32 = 0.7;
+// Share of divers that loop back into the formation instead of leaving for good
+const DIVE_REJOIN_CHANCE: f64 = 0.5;
+const ENEMY_HALF_WIDTH: f32 = 20.0;
+// Classic rules: how much faster than its base speed the formation gets once only one invader is left
+const CLASSIC_SPEEDUP: f32 = 3.0;
+const BULLET_CANCEL_SCORE: u32 = 10;
+const BUNKER_COUNT: usize = 4;
+const BUNKER_COLS: usize = 6;
+const BUNKER_ROWS: usize = 3;
+const BUNKER_SEGMENT_SIZE: f32 = 10.0;
+const ASTEROID_MIN_LEVEL: u32 = 3;
+const ASTEROID_INTERVAL: f32 = 6.0;
+const ASTEROID_SPEED: f32 = 70.0;
+const BOSS_LEVEL_INTERVAL: u32 = 5;
+const BOSS_HEALTH: u32 = 60;
+const BOSS_SIZE: Vec2 = Vec2::new(160.0, 60.0);
+const BOSS_Y: f32 = 200.0;
+const PLAYER_Y: f32 = -200.0;
+const BOSS_SWAY: f32 = 220.0;
+const BOSS_SWAY_SPEED: f32 = 0.6;
+const BOSS_INVULNERABLE_SECONDS: f32 = 1.5;
+const BOSS_VOLLEY_SHOTS: usize = 7;
+const BOSS_MINIONS: usize = 4;
+const BOSS_SWEEP_ARC: f32 = PI / 3.0;
+const BOSS_SWEEP_SPEED: f32 = 1.5;
+const BUNKER_Y: f32 = -140.0;
+const BUNKER_SEGMENT_HITS: u8 = 2;
+const FLY_IN_DURATION: f32 = 1.4;
+const FLY_IN_STAGGER: f32 = 0.12;
+const CONSOLE_LOG_LINES: usize = 6;
+const LOCALE_DIR: &str = "i18n";
+const ASSET_DIR: &str = "assets";
+const CONFIG_FILE: &str = "config.toml";
+const LEVEL_FILE: &str = "levels/formation.level.ron";
+const FONT_FILE: &str = "fonts/FiraSans-Bold.ttf";
+// Fira Sans Bold (SIL Open Font License) is compiled in and registered as Bevy's default font,
+// so text still renders without the assets folder. A FONT_FILE on disk or in mods overrides it
+const EMBEDDED_FONT: &[u8] = include_bytes!("../assets/fonts/FiraSans-Bold.ttf");
+// English ships inside the binary so missing keys or a missing assets folder still show readable text
+const FALLBACK_LOCALE: &str = include_str!("../assets/i18n/en.ron");
+
+// === COMPONENTS ===
+#[derive(Component)] 
+struct Player;
+#[derive(Component)] 
+struct Enemy;
+// Top-row elites occasionally swap their normal volley for a homing missile
+#[derive(Component)] 
+struct Elite;
+// Elites sidestep player bullets about to hit them, staying within half a cell of their slot
+#[derive(Component, Default)] 
+struct Evasive {
+    cooldown: Timer,
+    offset: f32,
+    target: f32,
+}
+// On the player's side: enemy bullets can hit it, and losing it loses the run (see escort.rs)
+#[derive(Component)] 
+struct Friendly;
+// Blue ring that absorbs one hit, then regenerates unless the invader is destroyed in the meantime
+#[derive(Component)] 
+struct RegenShield {
+    up: bool,
+    regen: Timer,
+    ring: Entity,
+}
+#[derive(Component)] 
+struct Bullet;
+// Neutral debris from level 3 on: soaks up shots from both sides and hurts the player on contact
+#[derive(Component)] 
+struct Asteroid {
+    spin: f32,
+}
+// Every fifth wave is a single boss in place of the formation
+#[derive(Component)] 
+struct Boss {
+    max_health: u32,
+    attack: Timer,
+    elapsed: f32,
+}
+// Boss attack pattern, picked from its remaining health
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)] 
+enum BossPhase {
+    Volley,
+    Summon,
+    Desperation,
+}
+// Hits are ignored until the timer runs out
+#[derive(Component)] 
+struct Invulnerable(Timer);
+#[derive(Component)] 
+struct EnemyBullet;
+// Pieces of a destroyed player ship, fading out as their Lifetime runs down
+#[derive(Component)] 
+struct Debris;
+#[derive(Component)] 
+struct ScoreText;
+#[derive(Component)] 
+struct LivesText;
+#[derive(Component)] 
+struct LevelText;
+#[derive(Component)] 
+struct WaveProgressText;
+#[derive(Component)] 
+struct GameOverText;
+#[derive(Component)] 
+struct BombsText;
+#[derive(Component)] 
+struct ScreenFlash(Timer);
+#[derive(Component)] 
+struct HeatBar;
+#[derive(Component)] 
+struct HeatBarFill;
+#[derive(Component)] 
+struct CooldownBar;
+#[derive(Component)] 
+struct CooldownBarFill;
+#[derive(Component)] 
+struct SettingsText;
+#[derive(Component)] 
+struct Localized(&'static str);
+#[derive(Component)] 
+struct LeaderboardText;
+#[derive(Component)] 
+struct DebugText;
+#[derive(Component)] 
+struct ConsoleText;
+#[derive(Component)] 
+struct CheatText;
+#[derive(Component)] 
+struct PauseOverlay;
+#[derive(Component)] 
+struct LevelIntroText;
+#[derive(Component)] 
+struct MainMenuText;
+#[derive(Component)] 
+struct RulesetText;
+#[derive(Component)] 
+struct DailyText;
+#[derive(Component)] 
+struct QuitDialogText;
+#[derive(Component)] 
+struct Velocity(Vec2);
+#[derive(Component)] 
+struct Health(u32);
+#[derive(Component, Clone, Copy)] 
+struct FlashOnHit {
+    tint: Color,
+}
+#[derive(Component)] 
+struct Flashing {
+    timer: Timer,
+    original: Color,
+}
+#[derive(Component, Clone, Copy, PartialEq)] 
+enum FirePattern {
+    Single,
+    Spread,
+    Burst,
+    Aimed,
+}
+// What a shooter's bullets look like and how they travel, like the arcade's three shot types
+#[derive(Component, Clone, Copy, PartialEq)] 
+enum ProjectileKind {
+    Wiggler,
+    Bolt,
+    ZigZag,
+    Homing,
+}
+#[derive(Component, Default)] 
+struct ProjectileAge(f32);
+#[derive(Component)] 
+struct WeaponState {
+    tier: u8,
+}
+#[derive(Component)] 
+struct Shield {
+    charged: bool,
+    recharge: Timer,
+}
+#[derive(Component)] 
+struct ShieldBubble;
+#[derive(Component)] 
+struct TrailSegment {
+    alpha: f32,
+}
+// "+100" text that drifts up from a kill and fades out
+#[derive(Component)] 
+struct ScorePopup;
+// Despawned once this far past any edge of the window, measured against its current size
+#[derive(Component)] 
+struct DespawnOutside(f32);
+// Despawned when the timer runs out
+#[derive(Component)] 
+struct Lifetime(Timer);
+#[derive(Component)] 
+struct Star {
+    speed: f32,
+}
+// Nebula wash and planet behind the starfield
+#[derive(Component)] 
+struct Backdrop;
+#[derive(Component, Default)] 
+struct Piercing {
+    hit: Vec<Entity>,
+}
+#[derive(Component, Clone, Copy, PartialEq, Debug)] 
+pub enum PowerUp {
+    WeaponUpgrade,
+    Laser,
+    SpreadShot,
+    Pierce,
+    // One extra life; only dropped between boss rush fights
+    Heal,
+}
+// Timed fan of three bullets per shot
+#[derive(Component)] 
+struct SpreadShot(Timer);
+// Timed piercing rounds: every normal shot fired meanwhile gets a `Pierce`
+#[derive(Component)] 
+struct PiercingRounds(Timer);
+// Kills a piercing round has left before it stops
+#[derive(Component)] 
+struct Pierce(u8);
+// Timed beam weapon on the player; `tick` paces the damage while the beam is on
+#[derive(Component)] 
+struct LaserWeapon {
+    remaining: Timer,
+    tick: Timer,
+}
+#[derive(Component)] 
+struct LaserBeam;
+// Belongs to the current wave; despawned in one place when the next wave starts or the run is left
+#[derive(Component)] 
+struct WaveScoped;
+#[derive(Component, Clone, Copy)] 
+struct FormationSlot {
+    col: usize,
+    row: usize,
+}
+#[derive(Component)] 
+struct ColumnCursor;
+#[derive(Component)] 
+struct Diving {
+    elapsed: f32,
+    swing: f32,
+    shoot_timer: Timer,
+    // Formation slot relative to EnemyMovement::drift, for divers that come back around
+    home: Vec2,
+    rejoin: bool,
+}
+#[derive(Component)] 
+struct BunkerSegment {
+    hits_left: u8,
+}
+// Swooping entrance from off-screen; t runs from a negative stagger delay up to 1
+#[derive(Component)] 
+struct FlyingIn {
+    from: Vec2,
+    target: Vec2,
+    t: f32,
+}
+
+// === STATES ===
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    // Waiting on GameAssets; moves on to the state in loading::LoadingTarget
+    Loading,
+    #[default]
+    Playing,
+    Paused,
+    // "WAVE N" banner and countdown; enemies hold still while the player lines up
+    LevelIntro,
+    MainMenu,
+}
+
+// === SYSTEM SETS ===
+// Each frame runs Input -> Movement -> Collision -> Cleanup -> Ui, so collisions see this frame's
+// transforms and restarts/new waves only spawn once everything else is done with the old entities
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    Input,
+    Movement,
+    Collision,
+    Cleanup,
+    Ui,
+}
+
+// === RESOURCES ===
+#[derive(Resource, Default)] 
+struct LevelIntroTimer(Timer);
+// Balance values read from assets/config.toml; anything missing falls back to the compiled-in defaults
+#[derive(Resource, Asset, TypePath, Clone, Deserialize)] 
+#[serde(default)]
+pub struct GameConfig {
+    pub player_speed: f32,
+    // Inertia movement only: speed gained per second while steering, and lost per second when coasting
+    pub player_acceleration: f32,
+    pub player_friction: f32,
+    pub bullet_speed: f32,
+    pub player_shoot_cooldown: f32,
+    pub charge_shot_time: f32,
+    pub enemy_speed: f32,
+    pub enemy_speed_per_level: f32,
+    pub enemy_step_down: f32,
+    pub enemy_bullet_speed: f32,
+    pub enemy_shoot_cooldown: f32,
+    pub max_enemy_shooters: u32,
+    pub power_up_drop_chance: f64,
+    pub bombs_per_level: u32,
+    pub shield_recharge_time: f32,
+    pub dive_interval: f32,
+    pub dive_speed: f32,
+}
+// Formation shape read from the level RON file
+#[derive(Resource, Asset, TypePath, Clone, Deserialize)] 
+pub struct LevelLayout {
+    pub rows: usize,
+    pub cols: usize,
+    pub spacing_x: f32,
+    pub spacing_y: f32,
+    pub start_y: f32,
+    // Optional rhai behaviour script per row, relative to the assets folder
+    #[serde(default)]
+    pub scripts: BTreeMap<usize, String>,
+    // Formation shapes cycled level by level, top line first. A digit is the enemy kind, i.e. the row
+    // it fights like (kind rows - 1 are elites), and '.' leaves the cell empty. None means a rows x cols block
+    #[serde(default)]
+    pub formations: Vec<Vec<String>>,
+    // Backdrops cycled through every LEVELS_PER_THEME levels
+    #[serde(default)]
+    pub themes: Vec<BackgroundTheme>,
+    // Extra rows that fly in while the wave is being fought, in order of `at`
+    #[serde(default)]
+    pub reinforcements: Vec<Reinforcement>,
+    // Enemy kinds that carry a regenerating shield ring
+    #[serde(default)]
+    pub regen_shields: Option<RegenShieldRule>,
+    // Levels where a cargo ship has to survive the wave
+    #[serde(default)]
+    pub escort: Option<EscortRule>,
+    // Levels fought upside down, with the player at the top
+    #[serde(default)]
+    pub gravity_flip: Option<GravityFlipRule>,
+}
+// A full row of the given formation row type, arriving `at` seconds into the wave above the formation
+#[derive(Clone, Deserialize)]
+pub struct Reinforcement {
+    pub at: f32,
+    pub row: usize,
+}
+// Which formation rows get a shield ring, from which level on
+#[derive(Clone, Deserialize)]
+pub struct RegenShieldRule {
+    pub from_level: u32,
+    pub rows: Vec<usize>,
+}
+// Every `every`th level (boss levels aside) is an escort, with a cargo ship that takes `health` hits
+#[derive(Clone, Deserialize)]
+pub struct EscortRule {
+    pub every: u32,
+    pub health: u32,
+}
+// Every `every`th level the playfield is mirrored vertically for the wave
+#[derive(Clone, Deserialize)]
+pub struct GravityFlipRule {
+    pub every: u32,
+}
+// Star tint and density, a full-screen nebula wash and an optional planet
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BackgroundTheme {
+    pub star_color: (f32, f32, f32),
+    pub star_density: f32,
+    pub nebula: (f32, f32, f32, f32),
+    pub planet: Option<Planet>,
+}
+#[derive(Clone, Deserialize)]
+pub struct Planet {
+    pub color: (f32, f32, f32),
+    pub radius: f32,
+    pub position: (f32, f32),
+}
+// Edited data files wait here until the current wave is over, so balance never shifts mid-fight
+#[derive(Resource, Default)] 
+struct StagedAssets {
+    config: Option<GameConfig>,
+    layout: Option<LevelLayout>,
+}
+// Textures, font and sound effects, loaded once when the plugin is built so spawns and UI setup just clone handles
+#[derive(Resource)] 
+pub struct GameAssets {
+    pub atlas: atlas::SpriteAtlas,
+    pub font: Handle<Font>,
+    pub sounds: sounds::SoundEffects,
+}
+#[derive(Resource)] 
+struct DataHandles {
+    config: Handle<GameConfig>,
+    layout: Handle<LevelLayout>,
+}
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct ShootTimer(Timer);
+// Seconds a fire press made during the cooldown stays queued
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct BufferedFire(f32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct EnemyMovement {
+    direction: f32,
+    // How far the march has carried the formation, so a slot can be found again after a dive
+    drift: Vec2,
+}
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+pub struct GameOver(pub bool);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct WaveWon(bool);
+// Set for the whole wave on gravity-flip levels: the ship sits at the top, the invaders close in from
+// below and every shot travels the other way. Anything that means "down" goes through `up()` and `y()`.
+#[derive(Resource, Clone, Copy, PartialEq, Default)] 
+struct GravityFlip(bool);
+// Zooms in on the last enemy of a wave before the summary comes up
+#[derive(Resource, Default)] 
+struct KillCam {
+    focus: Vec2,
+    timer: Timer,
+    active: bool,
+}
+// Real-time countdown from the final life being lost to the game over screen
+#[derive(Resource, Default)] 
+struct PendingGameOver(Option<Timer>);
+// Temporary slow motion on top of the game speed setting. It counts down in real time,
+// so UI fades and anything else on Time<Real> keeps its pace
+#[derive(Resource, Default)] 
+struct SlowMotion {
+    factor: f32,
+    remaining: f32,
+}
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+pub struct Score(pub u32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct EnemyShootTimer(Timer);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+pub struct PlayerLives(pub u32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+pub struct Level(pub u32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct EnemySpeed(f32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct DiveTimer(Timer);
+#[derive(Resource)] 
+struct AsteroidTimer(Timer);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct ChargeShot {
+    held: f32,
+}
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct Bombs(u32);
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct Heat {
+    level: f32,
+    lockout: Option<Timer>,
+}
+#[derive(Resource, Default, Serialize, Deserialize)] 
+#[serde(default)]
+struct Settings {
+    overheat: bool,
+    // Classic rule: no new shot while one of the player's bullets is still on screen. Always on
+    // under the Classic ruleset, whatever is stored here
+    one_bullet: bool,
+    language: Language,
+    palette: PaletteKind,
+    game_speed: GameSpeed,
+    controls: ControlScheme,
+    movement: MovementModel,
+    fire_mode: FireMode,
+    rumble: RumbleStrength,
+    frame_cap: FrameCap,
+    #[cfg(feature = "crt")]
+    crt: bool,
+}
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum Language {
+    #[default]
+    English,
+    Portuguese,
+    Spanish,
+}
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum GameSpeed {
+    #[default]
+    Full,
+    ThreeQuarters,
+    Half,
+}
+// Mouse mode: the ship chases the pointer and the left button fires alongside Space
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum ControlScheme {
+    #[default]
+    Keyboard,
+    Mouse,
+}
+// Classic moves at full speed the moment a direction is held; Inertia ramps the ship's Velocity
+// up and down using the config's acceleration and friction
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum MovementModel {
+    #[default]
+    Classic,
+    Inertia,
+}
+// Tap fires once per press and holding charges; Hold auto-fires at the cooldown rate while held;
+// Toggle starts and stops auto-fire with a press, for players who can't hold keys for long
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum FireMode {
+    #[default]
+    Tap,
+    Hold,
+    Toggle,
+}
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum RumbleStrength {
+    Off,
+    Half,
+    #[default]
+    Full,
+}
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum FrameCap {
+    Fps30,
+    Fps60,
+    #[default]
+    Uncapped,
+}
+// Set once a run has been played below full speed so its score can be told apart
+#[derive(Resource, Default)] 
+struct RunAssists {
+    reduced_speed: bool,
+}
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)] 
+enum PaletteKind {
+    #[default]
+    Classic,
+    Colorblind,
+    HighContrast,
+}
+#[derive(Resource)] 
+struct Palette {
+    kind: PaletteKind,
+    player_bullet: Color,
+    charged_bullet: Color,
+    enemy_bullet: Color,
+    enemy_wiggler: Color,
+    enemy_zigzag: Color,
+    enemy_homing: Color,
+    power_up: Color,
+    laser: Color,
+    spread_shot: Color,
+    pierce: Color,
+    heal: Color,
+    scrap: Color,
+    heat: Color,
+    heat_locked: Color,
+    shot_cooldown: Color,
+    shot_ready: Color,
+    shot_charge: Color,
+    lose_text: Color,
+    win_text: Color,
+    bunker: Color,
+    asteroid: Color,
+    bunker_damaged: Color,
+    // Enemy bullets become diamonds so they read apart from player shots without relying on hue
+    shaped_bullets: bool,
+}
+#[derive(Resource)] 
+struct Locale {
+    language: Language,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+#[derive(Resource, Default)] 
+struct SettingsMenu {
+    open: bool,
+    selected: usize,
+}
+// "Quit? Y/N" prompt raised by Escape on the main menu or by closing the window
+#[derive(Resource, Default)] 
+struct QuitDialog {
+    open: bool,
+}
+#[derive(Resource, Default)] 
+struct GameOverMenu {
+    selected: usize,
+    last_run: Option<RunFinished>,
+    submitted: bool,
+}
+// Per-run numbers shown on the game-over panel
+#[derive(Resource, Default, Reflect)] 
+#[reflect(Resource)]
+struct RunStats {
+    shots_fired: u32,
+    shots_hit: u32,
+    waves_cleared: u32,
+    combo: u32,
+    best_combo: u32,
+}
+
+impl RunStats {
+    fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        (self.shots_hit as f32 / self.shots_fired as f32).min(1.0) * 100.0
+    }
+
+    fn record_hit(&mut self) {
+        self.shots_hit += 1;
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+    }
+}
+// Snapshot of a run left mid-wave, written on "save and quit" and offered as Continue on the main menu
+#[derive(Serialize, Deserialize)] 
+struct SavedRun {
+    level: u32,
+    score: u32,
+    lives: u32,
+    bombs: u32,
+    weapon_tier: u8,
+    enemies: Vec<SavedEnemy>,
+    #[serde(default)]
+    new_game_plus: new_game_plus::NewGamePlus,
+    #[serde(default)]
+    ruleset: Ruleset,
+}
+#[derive(Serialize, Deserialize)] 
+struct SavedEnemy {
+    col: usize,
+    row: usize,
+    x: f32,
+    y: f32,
+    health: u32,
+    elite: bool,
+    #[serde(default)]
+    shielded: bool,
+}
+#[derive(Serialize, Deserialize, Clone)] 
+struct HighScoreEntry {
+    name: String,
+    score: u32,
+    level: u32,
+    mode: String,
+    #[serde(default)]
+    reduced_speed: bool,
+}
+#[derive(Resource, Default, Serialize, Deserialize)] 
+struct HighScores(Vec<HighScoreEntry>);
+#[derive(Resource)] 
+struct PlayerName(String);
+#[derive(Resource, Clone, Copy, PartialEq, Default, clap::ValueEnum)] 
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+// Picked on the main menu. Classic: one bullet at a time, a formation that only speeds up as it thins out,
+// no power-ups or bosses. Modern: everything the game has grown since, power-ups, combos and bosses included
+#[derive(Resource, Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)] 
+pub enum Ruleset {
+    Classic,
+    #[default]
+    Modern,
+}
+#[derive(Resource, Clone, Copy, PartialEq)] 
+enum GameMode {
+    Arcade,
+    Versus,
+    // Today's seeded run, see daily.rs
+    Daily,
+    // One endless wave, see survival.rs
+    Survival,
+    // Five waves against the clock, see time_attack.rs
+    TimeAttack,
+    // The boss fights back to back, see boss_rush.rs
+    BossRush,
+}
+#[derive(Resource)] 
+struct VersusControls {
+    column: usize,
+    fire_cooldown: Timer,
+    dive_cooldown: Timer,
+}
+// Horizontal edges of the playfield: the ship stops at them, or with `wrap` flies off one side and comes back on the other
+#[derive(Resource, Default)] 
+struct PlayfieldBounds {
+    wrap: bool,
+}
+#[derive(Resource, Default)] 
+struct LeaderboardScreen {
+    open: bool,
+}
+#[derive(Resource, Default)] 
+struct DebugOverlay {
+    open: bool,
+}
+#[derive(Resource, Default)] 
+struct DevConsole {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+}
+// Once any cheat fires the indicator stays up so recorded footage is identifiable
+#[derive(Resource, Default)] 
+struct Cheats {
+    god_mode: bool,
+    used: bool,
+}
+// All gameplay randomness goes through here so a seed reproduces a run
+#[derive(Resource)] 
+struct GameRng(StdRng);
+#[cfg(feature = "online-leaderboard")]
+#[derive(Resource)] 
+struct OnlineLeaderboard {
+    url: String,
+    scores: Option<Vec<leaderboard::OnlineScore>>,
+    task: Option<bevy::tasks::Task<Result<Vec<leaderboard::OnlineScore>, String>>>,
+}
+
+// Fighting time of the current wave (intro and pauses excluded) and the bonus its clear earned
+#[derive(Resource, Default)] 
+struct WaveClock {
+    elapsed: f32,
+    time_bonus: u32,
+}
+// Next reinforcement of the level's timeline still to arrive this wave
+#[derive(Resource, Default)] 
+struct WaveDirector {
+    next: usize,
+    // Survival only: wave clock time of the next generated row
+    next_row_at: f32,
+}
+// Enemies left in the current formation, for the HUD counter
+#[derive(Resource, Default)] 
+struct WaveProgress {
+    total: u32,
+    remaining: u32,
+}
+
+// === EVENTS ===
+#[derive(Event)] 
+struct EnemyHit {
+    enemy: Entity,
+    damage: u32,
+    charged: bool,
+    // Ignores regenerating shield rings (the console's kill_all)
+    through_shield: bool,
+}
+#[derive(Event)] 
+pub struct EnemyKilled {
+    pub elite: bool,
+    pub charged: bool,
+    pub position: Vec3,
+}
+// A player shot left the screen without hitting anything
+#[derive(Event)] 
+pub struct ShotMissed;
+#[derive(Event)] 
+pub struct ShotFired;
+#[derive(Event)] 
+pub struct PowerUpCollected(pub PowerUp);
+#[derive(Event)] 
+struct EnemyFireOrder {
+    column: Option<usize>,
+}
+#[derive(Event)] 
+struct DiveOrder {
+    column: Option<usize>,
+}
+#[derive(Event)] 
+pub struct WaveCleared;
+#[derive(Event, Clone, Copy)] 
+enum ConsoleCommand {
+    SpawnWave(u32),
+    SetLives(u32),
+    KillAll,
+    SetSpeed(f32),
+    Seed(u64),
+}
+#[derive(Event, Clone, Copy)] 
+pub struct RunFinished {
+    pub score: u32,
+    pub level: u32,
+    pub reduced_speed: bool,
+}
+// Wipes the board and starts over from wave 1
+#[derive(Event)] 
+pub struct RestartRun;
+#[cfg(feature = "online-leaderboard")]
+#[derive(Event)] 
+struct SubmitScore(RunFinished);
+#[derive(Event)] 
+pub struct Damaged {
+    pub entity: Entity,
+}
+// A friendly unit was destroyed
+#[derive(Event)] 
+struct FriendlyLost;
+// Something hit the player or the co-op partner; player_damage_handler decides between shield, respawn and game over
+#[derive(Event)] 
+pub struct PlayerDamagedEvent {
+    pub player: Entity,
+    // Ends the run outright, whatever lives and shield are left
+    pub lethal: bool,
+}
+
+impl ProjectileKind {
+    fn for_row(row: usize) -> Self {
+        match row {
+            0 | 1 => ProjectileKind::Wiggler,
+            2 => ProjectileKind::ZigZag,
+            _ => ProjectileKind::Bolt,
+        }
+    }
+
+    fn speed_scale(self) -> f32 {
+        match self {
+            ProjectileKind::Wiggler => 0.7,
+            ProjectileKind::Bolt => 1.5,
+            ProjectileKind::ZigZag => 1.0,
+            ProjectileKind::Homing => 0.8,
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            ProjectileKind::Wiggler => Vec2::new(7.0, 12.0),
+            ProjectileKind::Bolt => Vec2::new(3.0, 20.0),
+            ProjectileKind::ZigZag => Vec2::new(5.0, 14.0),
+            ProjectileKind::Homing => Vec2::new(6.0, 16.0),
+        }
+    }
+}
+
+impl FirePattern {
+    fn for_row(row: usize) -> Self {
+        match row {
+            0 | 1 => FirePattern::Single,
+            2 => FirePattern::Burst,
+            3 => FirePattern::Aimed,
+            _ => FirePattern::Spread,
+        }
+    }
+
+    // Fancier patterns are unlocked gradually so early levels keep the classic single shot
+    fn unlocked_at(self, level: u32) -> Self {
+        let required = match self {
+            FirePattern::Single => 1,
+            FirePattern::Burst => 2,
+            FirePattern::Aimed => 3,
+            FirePattern::Spread => 4,
+        };
+        if level >= required { self } else { FirePattern::Single }
+    }
+}
+
+// Elite rows at the top of the formation are armored and tinted so they stand out
+fn enemy_armor_for_row(row: usize) -> (u32, Color) {
+    match row {
+        4 => (3, Color::rgb(1.0, 0.55, 0.55)),
+        3 => (2, Color::rgb(1.0, 0.85, 0.5)),
+        _ => (1, Color::WHITE),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GameOverOption {
+    Retry,
+    MainMenu,
+    #[cfg(feature = "online-leaderboard")]
+    SubmitScore,
+}
+
+impl GameOverOption {
+    const ALL: &'static [GameOverOption] = &[
+        GameOverOption::Retry,
+        GameOverOption::MainMenu,
+        #[cfg(feature = "online-leaderboard")]
+        GameOverOption::SubmitScore,
+    ];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            GameOverOption::Retry => "game_over.retry",
+            GameOverOption::MainMenu => "game_over.main_menu",
+            #[cfg(feature = "online-leaderboard")]
+            GameOverOption::SubmitScore => "game_over.submit",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum SettingsItem {
+    Overheat,
+    OneBullet,
+    Language,
+    Palette,
+    GameSpeed,
+    Controls,
+    Movement,
+    FireMode,
+    Rumble,
+    FrameCap,
+    #[cfg(feature = "crt")]
+    Crt,
+}
+
+impl SettingsItem {
+    const ALL: &'static [SettingsItem] = &[
+        SettingsItem::Overheat,
+        SettingsItem::OneBullet,
+        SettingsItem::Language,
+        SettingsItem::Palette,
+        SettingsItem::GameSpeed,
+        SettingsItem::Controls,
+        SettingsItem::Movement,
+        SettingsItem::FireMode,
+        SettingsItem::Rumble,
+        SettingsItem::FrameCap,
+        #[cfg(feature = "crt")]
+        SettingsItem::Crt,
+    ];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            SettingsItem::Overheat => "settings.overheat",
+            SettingsItem::OneBullet => "settings.one_bullet",
+            SettingsItem::Language => "settings.language",
+            SettingsItem::Palette => "settings.palette",
+            SettingsItem::GameSpeed => "settings.game_speed",
+            SettingsItem::Controls => "settings.controls",
+            SettingsItem::Movement => "settings.movement",
+            SettingsItem::FireMode => "settings.fire_mode",
+            SettingsItem::Rumble => "settings.rumble",
+            SettingsItem::FrameCap => "settings.frame_cap",
+            #[cfg(feature = "crt")]
+            SettingsItem::Crt => "settings.crt",
+        }
+    }
+
+    fn value(self, settings: &Settings, locale: &Locale) -> String {
+        match self {
+            SettingsItem::Overheat => on_off(settings.overheat, locale).to_string(),
+            SettingsItem::OneBullet => on_off(settings.one_bullet, locale).to_string(),
+            SettingsItem::Language => settings.language.name().to_string(),
+            SettingsItem::Palette => locale.get(settings.palette.label_key()).to_string(),
+            SettingsItem::GameSpeed => format!("{}%", (settings.game_speed.factor() * 100.0) as u32),
+            SettingsItem::Controls => locale.get(settings.controls.label_key()).to_string(),
+            SettingsItem::Movement => locale.get(settings.movement.label_key()).to_string(),
+            SettingsItem::FireMode => locale.get(settings.fire_mode.label_key()).to_string(),
+            SettingsItem::Rumble => match settings.rumble {
+                RumbleStrength::Off => on_off(false, locale).to_string(),
+                strength => format!("{}%", (strength.factor() * 100.0) as u32),
+            },
+            SettingsItem::FrameCap => match settings.frame_cap.fps() {
+                Some(fps) => format!("{} FPS", fps as u32),
+                None => locale.get("frame_cap.uncapped").to_string(),
+            },
+            #[cfg(feature = "crt")]
+            SettingsItem::Crt => on_off(settings.crt, locale).to_string(),
+        }
+    }
+
+    fn change(self, settings: &mut Settings, step: i32) {
+        match self {
+            SettingsItem::Overheat => settings.overheat = !settings.overheat,
+            SettingsItem::OneBullet => settings.one_bullet = !settings.one_bullet,
+            SettingsItem::Language => settings.language = settings.language.cycle(step),
+            SettingsItem::Palette => settings.palette = settings.palette.cycle(step),
+            SettingsItem::GameSpeed => settings.game_speed = settings.game_speed.cycle(step),
+            SettingsItem::Controls => settings.controls = settings.controls.cycle(step),
+            SettingsItem::Movement => settings.movement = settings.movement.cycle(step),
+            SettingsItem::FireMode => settings.fire_mode = settings.fire_mode.cycle(step),
+            SettingsItem::Rumble => settings.rumble = settings.rumble.cycle(step),
+            SettingsItem::FrameCap => settings.frame_cap = settings.frame_cap.cycle(step),
+            #[cfg(feature = "crt")]
+            SettingsItem::Crt => settings.crt = !settings.crt,
+        }
+    }
+}
+
+fn on_off(value: bool, locale: &Locale) -> &str {
+    locale.get(if value { "settings.on" } else { "settings.off" })
+}
+
+impl Language {
+    const ALL: &'static [Language] = &[Language::English, Language::Portuguese, Language::Spanish];
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Portuguese => "pt",
+            Language::Spanish => "es",
+        }
+    }
+
+    // Shown in the language's own spelling so players can find theirs from any locale
+    fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Portuguese => "Português",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = Language::ALL.len() as i32;
+        let index = Language::ALL.iter().position(|language| *language == self).unwrap_or(0) as i32;
+        Language::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl GameSpeed {
+    const ALL: &'static [GameSpeed] = &[GameSpeed::Full, GameSpeed::ThreeQuarters, GameSpeed::Half];
+
+    fn factor(self) -> f32 {
+        match self {
+            GameSpeed::Full => 1.0,
+            GameSpeed::ThreeQuarters => 0.75,
+            GameSpeed::Half => 0.5,
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = GameSpeed::ALL.len() as i32;
+        let index = GameSpeed::ALL.iter().position(|speed| *speed == self).unwrap_or(0) as i32;
+        GameSpeed::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl ControlScheme {
+    const ALL: &'static [ControlScheme] = &[ControlScheme::Keyboard, ControlScheme::Mouse];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            ControlScheme::Keyboard => "controls.keyboard",
+            ControlScheme::Mouse => "controls.mouse",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = ControlScheme::ALL.len() as i32;
+        let index = ControlScheme::ALL.iter().position(|scheme| *scheme == self).unwrap_or(0) as i32;
+        ControlScheme::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl MovementModel {
+    const ALL: &'static [MovementModel] = &[MovementModel::Classic, MovementModel::Inertia];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            MovementModel::Classic => "movement.classic",
+            MovementModel::Inertia => "movement.inertia",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = MovementModel::ALL.len() as i32;
+        let index = MovementModel::ALL.iter().position(|model| *model == self).unwrap_or(0) as i32;
+        MovementModel::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl FireMode {
+    const ALL: &'static [FireMode] = &[FireMode::Tap, FireMode::Hold, FireMode::Toggle];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            FireMode::Tap => "fire_mode.tap",
+            FireMode::Hold => "fire_mode.hold",
+            FireMode::Toggle => "fire_mode.toggle",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = FireMode::ALL.len() as i32;
+        let index = FireMode::ALL.iter().position(|mode| *mode == self).unwrap_or(0) as i32;
+        FireMode::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl RumbleStrength {
+    const ALL: &'static [RumbleStrength] = &[RumbleStrength::Off, RumbleStrength::Half, RumbleStrength::Full];
+
+    fn factor(self) -> f32 {
+        match self {
+            RumbleStrength::Off => 0.0,
+            RumbleStrength::Half => 0.5,
+            RumbleStrength::Full => 1.0,
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = RumbleStrength::ALL.len() as i32;
+        let index = RumbleStrength::ALL.iter().position(|strength| *strength == self).unwrap_or(0) as i32;
+        RumbleStrength::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl FrameCap {
+    const ALL: &'static [FrameCap] = &[FrameCap::Fps30, FrameCap::Fps60, FrameCap::Uncapped];
+
+    fn fps(self) -> Option<f32> {
+        match self {
+            FrameCap::Fps30 => Some(30.0),
+            FrameCap::Fps60 => Some(60.0),
+            FrameCap::Uncapped => None,
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = FrameCap::ALL.len() as i32;
+        let index = FrameCap::ALL.iter().position(|cap| *cap == self).unwrap_or(0) as i32;
+        FrameCap::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl PaletteKind {
+    const ALL: &'static [PaletteKind] = &[PaletteKind::Classic, PaletteKind::Colorblind, PaletteKind::HighContrast];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            PaletteKind::Classic => "palette.classic",
+            PaletteKind::Colorblind => "palette.colorblind",
+            PaletteKind::HighContrast => "palette.high_contrast",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = PaletteKind::ALL.len() as i32;
+        let index = PaletteKind::ALL.iter().position(|kind| *kind == self).unwrap_or(0) as i32;
+        PaletteKind::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+impl Palette {
+    fn for_kind(kind: PaletteKind) -> Self {
+        match kind {
+            PaletteKind::Classic => Palette {
+                kind,
+                player_bullet: Color::WHITE,
+                charged_bullet: Color::rgb(0.5, 0.9, 1.0),
+                enemy_bullet: Color::YELLOW,
+                enemy_wiggler: Color::rgb(1.0, 0.75, 0.3),
+                enemy_zigzag: Color::rgb(1.0, 0.4, 0.8),
+                enemy_homing: Color::rgb(0.3, 1.0, 0.4),
+                power_up: Color::CYAN,
+                laser: Color::rgb(1.0, 0.2, 0.2),
+                spread_shot: Color::rgb(0.6, 1.0, 0.3),
+                pierce: Color::rgb(0.7, 0.5, 1.0),
+                heal: Color::rgb(1.0, 0.45, 0.55),
+                scrap: Color::rgb(0.75, 0.75, 0.8),
+                heat: Color::ORANGE,
+                heat_locked: Color::RED,
+                shot_cooldown: Color::GRAY,
+                shot_ready: Color::GREEN,
+                shot_charge: Color::CYAN,
+                lose_text: Color::RED,
+                win_text: Color::GREEN,
+                bunker: Color::rgb(0.2, 0.85, 0.2),
+                asteroid: Color::rgb(0.55, 0.45, 0.35),
+                bunker_damaged: Color::rgb(0.1, 0.4, 0.1),
+                shaped_bullets: false,
+            },
+            // Okabe-Ito blue/orange pair, which stays distinct for the common forms of color blindness
+            PaletteKind::Colorblind => Palette {
+                kind,
+                player_bullet: Color::rgb(0.34, 0.71, 0.91),
+                charged_bullet: Color::WHITE,
+                enemy_bullet: Color::rgb(0.9, 0.6, 0.0),
+                enemy_wiggler: Color::rgb(0.94, 0.89, 0.26),
+                enemy_zigzag: Color::rgb(0.84, 0.37, 0.0),
+                enemy_homing: Color::rgb(0.35, 0.7, 0.9),
+                power_up: Color::rgb(0.8, 0.47, 0.65),
+                laser: Color::rgb(0.0, 0.45, 0.7),
+                spread_shot: Color::rgb(0.34, 0.71, 0.91),
+                pierce: Color::rgb(0.8, 0.8, 0.8),
+                heal: Color::rgb(0.0, 0.62, 0.45),
+                scrap: Color::rgb(0.94, 0.89, 0.26),
+                heat: Color::rgb(0.9, 0.6, 0.0),
+                heat_locked: Color::rgb(0.84, 0.37, 0.0),
+                shot_cooldown: Color::rgb(0.6, 0.6, 0.6),
+                shot_ready: Color::rgb(0.0, 0.45, 0.7),
+                shot_charge: Color::rgb(0.8, 0.6, 0.7),
+                lose_text: Color::rgb(0.84, 0.37, 0.0),
+                win_text: Color::rgb(0.34, 0.71, 0.91),
+                bunker: Color::rgb(0.0, 0.62, 0.45),
+                asteroid: Color::rgb(0.6, 0.6, 0.6),
+                bunker_damaged: Color::rgb(0.0, 0.3, 0.22),
+                shaped_bullets: true,
+            },
+            PaletteKind::HighContrast => Palette {
+                kind,
+                player_bullet: Color::WHITE,
+                charged_bullet: Color::CYAN,
+                enemy_bullet: Color::rgb(1.0, 0.0, 1.0),
+                enemy_wiggler: Color::YELLOW,
+                enemy_zigzag: Color::rgb(1.0, 0.2, 0.2),
+                enemy_homing: Color::CYAN,
+                power_up: Color::rgb(0.0, 1.0, 0.0),
+                laser: Color::rgb(1.0, 0.0, 0.0),
+                spread_shot: Color::rgb(1.0, 0.5, 0.0),
+                pierce: Color::rgb(0.6, 0.6, 1.0),
+                heal: Color::rgb(1.0, 0.6, 0.8),
+                scrap: Color::rgb(1.0, 1.0, 1.0),
+                heat: Color::YELLOW,
+                heat_locked: Color::rgb(1.0, 0.0, 1.0),
+                shot_cooldown: Color::rgb(0.5, 0.5, 0.5),
+                shot_ready: Color::WHITE,
+                shot_charge: Color::CYAN,
+                lose_text: Color::YELLOW,
+                win_text: Color::WHITE,
+                bunker: Color::WHITE,
+                asteroid: Color::rgb(0.5, 0.5, 0.5),
+                bunker_damaged: Color::rgb(0.45, 0.45, 0.45),
+                shaped_bullets: true,
+            },
+        }
+    }
+
+    fn power_up_color(&self, kind: PowerUp) -> Color {
+        match kind {
+            PowerUp::WeaponUpgrade => self.power_up,
+            PowerUp::Laser => self.laser,
+            PowerUp::SpreadShot => self.spread_shot,
+            PowerUp::Pierce => self.pierce,
+            PowerUp::Heal => self.heal,
+        }
+    }
+
+    fn enemy_bullet_color(&self, kind: ProjectileKind) -> Color {
+        match kind {
+            ProjectileKind::Wiggler => self.enemy_wiggler,
+            ProjectileKind::Bolt => self.enemy_bullet,
+            ProjectileKind::ZigZag => self.enemy_zigzag,
+            ProjectileKind::Homing => self.enemy_homing,
+        }
+    }
+
+    fn enemy_bullet_size(&self, kind: ProjectileKind) -> Vec2 {
+        if self.shaped_bullets { Vec2::splat(10.0) } else { kind.size() }
+    }
+
+    // Sprites point straight down by default, so rotate them to face along their velocity
+    fn enemy_bullet_rotation(&self, velocity: Vec2) -> Quat {
+        let rotation = Quat::from_rotation_z(velocity.y.atan2(velocity.x) + PI / 2.0);
+        if self.shaped_bullets { rotation * Quat::from_rotation_z(PI / 4.0) } else { rotation }
+    }
+}
+
+impl Locale {
+    fn load(language: Language) -> Self {
+        let fallback: HashMap<String, String> = ron::from_str(FALLBACK_LOCALE).unwrap_or_default();
+        let path = format!("{}/{}.ron", LOCALE_DIR, language.code());
+        let strings = match mods::read_data_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("Could not parse {path}: {error}");
+                HashMap::new()
+            }),
+            Err(error) => {
+                warn!("Could not read {path}: {error}");
+                HashMap::new()
+            }
+        };
+        Locale { language, strings, fallback }
+    }
+
+    // Missing keys fall back to English, then to the key itself so gaps are easy to spot
+    fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
+    }
+}
+
+impl Settings {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.config_file(SETTINGS_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.config_file(SETTINGS_FILE), self, "settings");
+    }
+}
+
+impl SavedRun {
+    fn exists(profile: &CurrentProfile) -> bool {
+        profile.data_file(SAVE_FILE).exists()
+    }
+
+    // Loading consumes the save so a run can only be resumed once
+    fn take(profile: &CurrentProfile) -> Option<Self> {
+        let path = profile.data_file(SAVE_FILE);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        if let Err(error) = std::fs::remove_file(&path) {
+            warn!("Could not remove {SAVE_FILE}: {error}");
+        }
+        match ron::from_str(&contents) {
+            Ok(run) => Some(run),
+            Err(error) => {
+                warn!("Could not parse {SAVE_FILE}: {error}");
+                None
+            }
+        }
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(SAVE_FILE), self, "the run");
+    }
+}
+
+impl HighScores {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(HIGH_SCORES_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(HIGH_SCORES_FILE), self, "high scores");
+    }
+
+    fn insert(&mut self, entry: HighScoreEntry) {
+        self.0.push(entry);
+        self.0.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        self.0.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            player_speed: 300.0,
+            player_acceleration: 1200.0,
+            player_friction: 900.0,
+            bullet_speed: 500.0,
+            player_shoot_cooldown: 0.3,
+            charge_shot_time: 1.0,
+            enemy_speed: 100.0,
+            enemy_speed_per_level: 50.0,
+            enemy_step_down: 20.0,
+            enemy_bullet_speed: 250.0,
+            enemy_shoot_cooldown: 1.2,
+            max_enemy_shooters: 4,
+            power_up_drop_chance: 0.08,
+            bombs_per_level: 1,
+            shield_recharge_time: 10.0,
+            dive_interval: 4.0,
+            dive_speed: 220.0,
+        }
+    }
+}
+
+impl GameConfig {
+    // Read synchronously so the very first wave already uses the file; later edits arrive through the asset server
+    pub fn load() -> Self {
+        match mods::read_data_to_string(CONFIG_FILE) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|error| {
+                warn!("Could not parse {CONFIG_FILE}, using defaults: {error}");
+                GameConfig::default()
+            }),
+            Err(_) => GameConfig::default(),
+        }
+    }
+
+    fn enemy_speed_for_level(&self, level: u32) -> f32 {
+        self.enemy_speed + self.enemy_speed_per_level * level.saturating_sub(1) as f32
+    }
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let sounds = sounds::SoundEffects::from_world(world);
+        let atlas = atlas::SpriteAtlas::build(world);
+        let embedded_font = Font::try_from_bytes(EMBEDDED_FONT.to_vec()).expect("embedded font is a valid TTF");
+        world.resource_mut::<Assets<Font>>().insert(AssetId::<Font>::default(), embedded_font);
+        let asset_server = world.resource::<AssetServer>();
+        let font = if mods::data_exists(FONT_FILE) { asset_server.load(FONT_FILE) } else { Handle::default() };
+        GameAssets { atlas, font, sounds }
+    }
+}
+
+impl Default for LevelLayout {
+    fn default() -> Self {
+        LevelLayout {
+            rows: 5,
+            cols: 8,
+            spacing_x: 60.0,
+            spacing_y: 40.0,
+            start_y: 100.0,
+            scripts: BTreeMap::new(),
+            formations: Vec::new(),
+            themes: Vec::new(),
+            reinforcements: Vec::new(),
+            regen_shields: None,
+            escort: None,
+            gravity_flip: None,
+        }
+    }
+}
+
+impl Default for BackgroundTheme {
+    fn default() -> Self {
+        BackgroundTheme {
+            star_color: (1.0, 1.0, 1.0),
+            star_density: 1.0,
+            nebula: (0.0, 0.0, 0.0, 0.0),
+            planet: None,
+        }
+    }
+}
+
+impl LevelLayout {
+    pub fn load() -> Self {
+        match mods::read_data_to_string(LEVEL_FILE) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("Could not parse {LEVEL_FILE}, using the default formation: {error}");
+                LevelLayout::default()
+            }),
+            Err(_) => LevelLayout::default(),
+        }
+    }
+
+    // The cargo ship's health on escort levels, None everywhere else
+    fn escort_health(&self, ruleset: Ruleset, level: u32) -> Option<u32> {
+        let rule = self.escort.as_ref()?;
+        let escort_level = rule.every > 0 && level.is_multiple_of(rule.every) && !is_boss_level(ruleset, level);
+        escort_level.then_some(rule.health.max(1))
+    }
+
+    fn gravity_flipped(&self, level: u32) -> bool {
+        self.gravity_flip.as_ref().is_some_and(|rule| rule.every > 0 && level.is_multiple_of(rule.every))
+    }
+
+    fn has_regen_shield(&self, row: usize, level: u32) -> bool {
+        self.regen_shields.as_ref().is_some_and(|rule| level >= rule.from_level && rule.rows.contains(&row))
+    }
+
+    // (col, row counted from the bottom, enemy kind) of every enemy in the level's formation
+    fn cells_for_level(&self, level: u32) -> Vec<(usize, usize, usize)> {
+        if self.formations.is_empty() {
+            return (0..self.rows).flat_map(|row| (0..self.cols).map(move |col| (col, row, row))).collect();
+        }
+        let grid = &self.formations[level.saturating_sub(1) as usize % self.formations.len()];
+        grid.iter()
+            .rev()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter_map(move |(col, code)| code.to_digit(10).map(|kind| (col, row, kind as usize)))
+            })
+            .collect()
+    }
+
+    fn formation_width(&self, level: u32) -> usize {
+        self.cells_for_level(level).iter().map(|&(col, _, _)| col + 1).max().unwrap_or(self.cols)
+    }
+
+    // x of column 0, keeping a formation this many columns wide centred
+    fn start_x(&self, width: usize) -> f32 {
+        -(width as f32 / 2.0) * self.spacing_x + self.spacing_x / 2.0
+    }
+
+    fn theme_for_level(&self, level: u32) -> BackgroundTheme {
+        if self.themes.is_empty() {
+            return BackgroundTheme::default();
+        }
+        let group = level.saturating_sub(1) / LEVELS_PER_THEME;
+        self.themes[group as usize % self.themes.len()].clone()
+    }
+
+    fn warn_unsupported_scripts(&self) {
+        if !self.scripts.is_empty() && !cfg!(feature = "scripting") {
+            warn!("{LEVEL_FILE} lists enemy scripts, but this build has no `scripting` feature; ignoring them");
+        }
+    }
+}
+
+impl Difficulty {
+    fn label_key(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+
+    fn enemy_speed_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    fn enemy_fire_rate(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    fn aimed_shot_chance(self) -> f64 {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => 0.0,
+            Difficulty::Hard => 0.35,
+        }
+    }
+}
+
+impl Ruleset {
+    const ALL: &'static [Ruleset] = &[Ruleset::Classic, Ruleset::Modern];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Ruleset::Classic => "ruleset.classic",
+            Ruleset::Modern => "ruleset.modern",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        let count = Ruleset::ALL.len() as i32;
+        let index = Ruleset::ALL.iter().position(|ruleset| *ruleset == self).unwrap_or(0) as i32;
+        Ruleset::ALL[(index + step).rem_euclid(count) as usize]
+    }
+
+    fn power_ups(self) -> bool {
+        self == Ruleset::Modern
+    }
+
+    fn combos(self) -> bool {
+        self == Ruleset::Modern
+    }
+
+    fn bosses(self) -> bool {
+        self == Ruleset::Modern
+    }
+}
+
+impl GameMode {
+    fn name(self) -> &'static str {
+        match self {
+            GameMode::Arcade => "arcade",
+            GameMode::Versus => "versus",
+            GameMode::Daily => "daily",
+            GameMode::Survival => "survival",
+            GameMode::TimeAttack => "time_attack",
+            GameMode::BossRush => "boss_rush",
+        }
+    }
+}
+
+impl GameRng {
+    fn seeded(seed: u64) -> Self {
+        println!("Game RNG seed: {}", seed);
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl ConsoleCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut parts = line.split_whitespace();
+        let name = parts.next().unwrap_or_default();
+        let mut argument = || {
+            parts
+                .next()
+                .ok_or_else(|| format!("{name} expects a value"))
+                .map(str::to_string)
+        };
+        let invalid = |value: String| format!("{name}: invalid value '{value}'");
+        match name {
+            "spawn_wave" => {
+                let value = argument()?;
+                value.parse().map(ConsoleCommand::SpawnWave).map_err(|_| invalid(value))
+            }
+            "set_lives" => {
+                let value = argument()?;
+                value.parse().map(ConsoleCommand::SetLives).map_err(|_| invalid(value))
+            }
+            "kill_all" => Ok(ConsoleCommand::KillAll),
+            "set_speed" => {
+                let value = argument()?;
+                value.parse().map(ConsoleCommand::SetSpeed).map_err(|_| invalid(value))
+            }
+            "seed" => {
+                let value = argument()?;
+                value.parse().map(ConsoleCommand::Seed).map_err(|_| invalid(value))
+            }
+            "" => Err(String::new()),
+            _ => Err(format!("Unknown command '{name}' (spawn_wave, set_lives, kill_all, set_speed, seed)")),
+        }
+    }
+}
+
+impl PlayerName {
+    // Named profiles sign their scores; the default one goes by the OS user
+    fn for_profile(profile: &CurrentProfile) -> Self {
+        if profile.is_default() { Self::from_env() } else { PlayerName(profile.0.clone()) }
+    }
+
+    fn from_env() -> Self {
+        let name = std::env::var("SPACE_INVADERS_NAME")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_else(|_| "PLAYER".to_string());
+        PlayerName(name)
+    }
+}
+
+// === PLUGIN ===
+// The whole game as one plugin, so other Bevy apps can embed it. The host app provides
+// DefaultPlugins (and, for headless runs, its own schedule runner); GameConfig and
+// LevelLayout resources inserted before this plugin replace the ones read from assets/.
+pub struct SpaceInvadersPlugin {
+    pub level: u32,
+    pub seed: Option<u64>,
+    pub difficulty: Difficulty,
+    pub versus: bool,
+    pub survival: bool,
+    pub time_attack: bool,
+    // LAN co-op: host on this UDP port, or join the host at this address
+    pub host: Option<u16>,
+    pub join: Option<SocketAddr>,
+    pub telemetry: Option<PathBuf>,
+    // Screen-wrap modifier for the player ship
+    pub wrap: bool,
+    // Start straight into a run instead of on the main menu
+    pub skip_main_menu: bool,
+}
+
+impl Default for SpaceInvadersPlugin {
+    fn default() -> Self {
+        SpaceInvadersPlugin {
+            level: 1,
+            seed: None,
+            difficulty: Difficulty::Normal,
+            versus: false,
+            survival: false,
+            time_attack: false,
+            host: None,
+            join: None,
+            telemetry: None,
+            wrap: false,
+            skip_main_menu: false,
+        }
+    }
+}
+
+impl Plugin for SpaceInvadersPlugin {
+    fn build(&self, app: &mut App) {
+        let config = app.world.get_resource::<GameConfig>().cloned().unwrap_or_else(GameConfig::load);
+        let profile = CurrentProfile::load();
+        let settings = Settings::load(&profile);
+        let layout = app.world.get_resource::<LevelLayout>().cloned().unwrap_or_else(LevelLayout::load);
+        layout.warn_unsupported_scripts();
+
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+
+        let role = net::role_from_options(self.host, self.join);
+        // Co-op clients only mirror the host, so they never run the intro that builds the first wave
+        let initial_state = if matches!(role, Some(net::NetRole::Client { .. })) {
+            GameState::Playing
+        } else if self.skip_main_menu {
+            GameState::LevelIntro
+        } else {
+            GameState::MainMenu
+        };
+        app.insert_state(GameState::Loading)
+            .insert_resource(loading::LoadingTarget(initial_state))
+            .init_resource::<LevelIntroTimer>()
+            .insert_resource(config.clone())
+            .insert_resource(layout)
+            .init_resource::<StagedAssets>()
+            .init_resource::<GameAssets>()
+            .init_asset::<GameConfig>()
+            .init_asset::<LevelLayout>()
+            .register_asset_loader(loaders::TomlLoader::<GameConfig>::default())
+            .register_asset_loader(loaders::RonLoader::<LevelLayout>::new(&["level.ron"]))
+            .add_systems(Startup, (setup_camera, load_data_files, setup_score_ui, setup_lives_ui, setup_level_ui, setup_bombs_ui, setup_wave_progress_ui, setup_heat_ui, setup_cooldown_ui, setup_settings_ui, setup_leaderboard_ui, setup_debug_ui, setup_console_ui, setup_cheat_ui, setup_column_cursor, setup_quit_dialog_ui))
+            .insert_resource(ShootTimer(Timer::from_seconds(config.player_shoot_cooldown, TimerMode::Once)))
+            .init_resource::<BufferedFire>()
+            .insert_resource(EnemyMovement {
+                direction: 1.0,
+                drift: Vec2::ZERO,
+            })
+            .insert_resource(GameOver(false))
+            .insert_resource(WaveWon(false))
+            .init_resource::<GravityFlip>()
+            .insert_resource(Score(0))
+            .insert_resource(EnemyShootTimer(Timer::from_seconds(config.enemy_shoot_cooldown, TimerMode::Repeating)))
+            .insert_resource(PlayerLives(3))
+            .insert_resource(Level(self.level))
+            .insert_resource(EnemySpeed(config.enemy_speed_for_level(self.level)))
+            .insert_resource(self.difficulty)
+            .init_resource::<ChargeShot>()
+            .insert_resource(Bombs(config.bombs_per_level))
+            .init_resource::<Heat>()
+            .insert_resource(Locale::load(settings.language))
+            .insert_resource(Palette::for_kind(settings.palette))
+            .insert_resource(settings)
+            .init_resource::<SettingsMenu>()
+            .init_resource::<QuitDialog>()
+            .init_resource::<RunAssists>()
+            .add_event::<EnemyHit>()
+            .add_event::<EnemyKilled>()
+            .add_event::<ShotMissed>()
+            .add_event::<ShotFired>()
+            .add_event::<PowerUpCollected>()
+            .add_event::<Damaged>()
+            .add_event::<FriendlyLost>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<WaveCleared>()
+            .add_event::<RunFinished>()
+            .add_event::<RestartRun>()
+            .init_resource::<GameOverMenu>()
+            .init_resource::<RunStats>()
+            .init_resource::<WaveProgress>()
+            .init_resource::<WaveClock>()
+            .init_resource::<WaveDirector>()
+            .init_resource::<KillCam>()
+            .init_resource::<SlowMotion>()
+            .init_resource::<PendingGameOver>()
+            .insert_resource(HighScores::load(&profile))
+            .insert_resource(PlayerName::for_profile(&profile))
+            .insert_resource(profile)
+            .init_resource::<LeaderboardScreen>()
+            .init_resource::<DebugOverlay>()
+            .init_resource::<DevConsole>()
+            .init_resource::<Cheats>()
+            .insert_resource(GameRng::seeded(self.seed.unwrap_or_else(|| rand::rng().random())))
+            .add_event::<ConsoleCommand>()
+            .insert_resource(if self.versus {
+                GameMode::Versus
+            } else if self.survival {
+                GameMode::Survival
+            } else if self.time_attack {
+                GameMode::TimeAttack
+            } else {
+                GameMode::Arcade
+            })
+            .insert_resource(PlayfieldBounds { wrap: self.wrap })
+            .init_resource::<Ruleset>()
+            .insert_resource(VersusControls {
+                column: 0,
+                fire_cooldown: Timer::from_seconds(VERSUS_FIRE_COOLDOWN, TimerMode::Once),
+                dive_cooldown: Timer::from_seconds(VERSUS_DIVE_COOLDOWN, TimerMode::Once),
+            })
+            .add_event::<EnemyFireOrder>()
+            .add_event::<DiveOrder>()
+            .insert_resource(DiveTimer(Timer::from_seconds(config.dive_interval, TimerMode::Repeating)))
+            .insert_resource(AsteroidTimer(Timer::from_seconds(ASTEROID_INTERVAL, TimerMode::Repeating)))
+            .configure_sets(Update, (
+                GameplaySet::Input,
+                GameplaySet::Movement,
+                GameplaySet::Collision,
+                GameplaySet::Cleanup,
+                GameplaySet::Ui,
+            ).chain())
+            .add_systems(Update, (
+                (
+                    fire_bullet.run_if(settings_closed).run_if(not(random_events::emp_active)),
+                    fire_laser.run_if(settings_closed).run_if(not(random_events::emp_active)),
+                    versus_controls.run_if(versus_mode),
+                    use_smart_bomb.run_if(settings_closed),
+                ).in_set(GameplaySet::Input).run_if(not(game_over)),
+                (
+                    bullet_movement,
+                    enemy_movement,
+                    enemy_fire_bullet.run_if(not(versus_mode)).run_if(not(random_events::emp_active)),
+                    enemy_bullet_movement,
+                    start_enemy_dive.run_if(not(versus_mode)),
+                    execute_enemy_fire.after(enemy_fire_bullet),
+                    execute_dive.after(start_enemy_dive),
+                    diving_enemy_movement,
+                    power_up_movement,
+                    attach_player_shield,
+                    recharge_shield,
+                    cool_weapon,
+                    tick_wave_clock,
+                    run_wave_director.after(tick_wave_clock),
+                    evade_player_bullets.after(enemy_movement),
+                    spawn_asteroids,
+                    (asteroid_movement, debris_movement),
+                    boss_movement,
+                    boss_attack.after(boss_movement).run_if(not(random_events::emp_active)),
+                    tick_invulnerable,
+                ).in_set(GameplaySet::Movement).run_if(not(game_over)),
+                (
+                    bullet_enemy_collision,
+                    bullet_bullet_collision,
+                    bullet_bunker_collision,
+                    bullet_asteroid_collision,
+                    asteroid_player_collision.before(player_damage_handler).run_if(not(god_mode)),
+                    enemy_bullet_player_collision.run_if(not(god_mode)),
+                    player_damage_handler.after(enemy_bullet_player_collision),
+                    finish_pending_game_over.after(player_damage_handler),
+                    enemy_player_collision.before(player_damage_handler).run_if(not(god_mode)),
+                    collect_power_ups,
+                    apply_enemy_hits.after(bullet_enemy_collision),
+                    regenerate_enemy_shields.after(apply_enemy_hits),
+                    update_boss_phase.after(apply_enemy_hits),
+                    check_game_over,
+                    check_win_condition.run_if(not(survival::survival_mode)),
+                    on_wave_cleared.after(check_win_condition),
+                ).in_set(GameplaySet::Collision).run_if(not(game_over)),
+                // These react to the game ending, so they keep running once it has
+                (
+                    detect_run_finished.after(on_wave_cleared),
+                    record_high_score.after(detect_run_finished),
+                    track_run_assists.before(detect_run_finished),
+                ).in_set(GameplaySet::Collision),
+                expire_power_ups.in_set(GameplaySet::Cleanup).run_if(not(game_over)),
+                next_level.in_set(GameplaySet::Cleanup),
+            ).run_if(not(net::is_client)).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (
+                player_movement,
+                fly_in_enemies,
+            ).in_set(GameplaySet::Movement).run_if(not(game_over)).run_if(not(net::is_client)).run_if(in_state(GameState::Playing).or_else(in_state(GameState::LevelIntro))))
+            .add_systems(Update, (
+                // Restarts can come from the game over screen or the main menu
+                restart_game,
+                tick_level_intro.run_if(in_state(GameState::LevelIntro)),
+            ).in_set(GameplaySet::Cleanup).run_if(not(net::is_client)))
+            .add_systems(Update, (
+                navigate_game_over_menu.run_if(in_state(GameState::Playing)),
+                start_from_main_menu.run_if(in_state(GameState::MainMenu)),
+                resume_saved_run.run_if(in_state(GameState::MainMenu)),
+                (select_ruleset, update_ruleset_text).chain().run_if(in_state(GameState::MainMenu)),
+            ).in_set(GameplaySet::Input).run_if(not(net::is_client)))
+            .add_systems(Update, (
+                toggle_settings_menu,
+                navigate_settings_menu,
+                toggle_leaderboard,
+                toggle_pause,
+                auto_pause_on_focus_loss,
+                toggle_debug_overlay,
+                dev_console_input,
+                apply_console_commands.after(dev_console_input),
+                request_quit,
+                answer_quit_dialog.after(request_quit),
+            ).in_set(GameplaySet::Input))
+            .add_systems(PreUpdate, swallow_console_input.after(InputSystem))
+            .add_systems(Update, (stage_reloaded_data, apply_staged_data.after(stage_reloaded_data)).in_set(GameplaySet::Cleanup))
+            .add_systems(Update, (despawn_outside_window, expire_lifetimes).in_set(GameplaySet::Cleanup))
+            .add_systems(Update, (
+                start_hit_flash,
+                update_hit_flash,
+                update_screen_flash,
+                spawn_starfield,
+                scroll_starfield,
+                spawn_bullet_trails,
+                fade_bullet_trails,
+                update_score_popups,
+                update_column_cursor,
+            ).in_set(GameplaySet::Ui))
+            .add_systems(Update, (
+                update_score_text,
+                update_lives_text,
+                update_level_text,
+                update_bombs_text,
+                update_wave_progress,
+                update_heat_bar,
+                update_cooldown_bar,
+                game_over_screen,
+                update_settings_text,
+                update_leaderboard_text,
+                sync_virtual_time,
+                reload_locale,
+                apply_localized_text.after(reload_locale),
+                update_palette,
+                recolor_with_palette.after(update_palette),
+                update_debug_text,
+                update_console_text,
+                update_cheat_text,
+                update_quit_dialog_text,
+            ).in_set(GameplaySet::Ui))
+            .add_systems(Update, update_kill_cam.in_set(GameplaySet::Ui))
+            .add_systems(Update, mirror_flipped_sprites.in_set(GameplaySet::Cleanup))
+            .add_systems(OnEnter(GameState::MainMenu), (show_main_menu, despawn_wave_entities, reset_gravity_flip))
+            // The continue line and profile name depend on the profile, so switching rebuilds the menu
+            .add_systems(Update, reload_profile_data.in_set(GameplaySet::Input))
+            .add_systems(
+                Update,
+                (hide_main_menu, show_main_menu)
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu).and_then(resource_changed::<CurrentProfile>))
+                    .in_set(GameplaySet::Ui),
+            )
+            .add_systems(OnExit(GameState::MainMenu), hide_main_menu)
+            .add_systems(OnEnter(GameState::LevelIntro), (
+                show_level_intro,
+                (
+                    despawn_wave_entities,
+                    set_gravity_flip,
+                    (spawn_wave, spawn_bunkers).run_if(not(net::is_client)),
+                ).chain(),
+            ))
+            .add_systems(OnExit(GameState::LevelIntro), hide_level_intro)
+            .add_systems(OnEnter(GameState::Paused), show_pause_overlay)
+            .add_systems(OnExit(GameState::Paused), hide_pause_overlay)
+            .add_plugins(net::NetPlugin)
+            .add_plugins(achievements::AchievementsPlugin)
+            .add_plugins(player_stats::PlayerStatsPlugin)
+            .add_plugins(profiles::ProfilesPlugin)
+            .add_plugins(daily::DailyPlugin)
+            .add_plugins(survival::SurvivalPlugin)
+            .add_plugins(time_attack::TimeAttackPlugin)
+            .add_plugins(boss_rush::BossRushPlugin)
+            .add_plugins(new_game_plus::NewGamePlusPlugin)
+            .add_plugins(scrap::ScrapPlugin)
+            .add_plugins(random_events::RandomEventsPlugin)
+            .add_plugins(escort::EscortPlugin)
+            .add_plugins(screenshots::ScreenshotsPlugin)
+            .add_plugins(sounds::SoundsPlugin)
+            .add_plugins(rumble::RumblePlugin)
+            .add_plugins(input::InputPlugin)
+            .add_plugins(atlas::AtlasPlugin)
+            .add_plugins(loading::LoadingPlugin)
+            .add_plugins(frame_pacing::FramePacingPlugin);
+
+        #[cfg(feature = "scripting")]
+        app.add_plugins(scripting::ScriptingPlugin);
+
+        if let Some(dir) = self.telemetry.clone() {
+            app.add_plugins(telemetry::TelemetryPlugin { dir });
+        }
+
+        if let Some(role) = role {
+            app.insert_resource(role);
+        }
+
+        #[cfg(feature = "online-leaderboard")]
+        app.insert_resource(OnlineLeaderboard {
+            url: std::env::var("SPACE_INVADERS_LEADERBOARD_URL")
+                .unwrap_or_else(|_| "http://localhost:8080/scores".to_string()),
+            scores: None,
+            task: None,
+        })
+        .add_event::<SubmitScore>()
+        .add_systems(Update, (
+            submit_online_score.in_set(GameplaySet::Input).after(navigate_game_over_menu),
+            (fetch_online_leaderboard, poll_online_leaderboard).in_set(GameplaySet::Ui),
+        ));
+
+        #[cfg(debug_assertions)]
+        app.add_systems(PreUpdate, cheat_chords.after(swallow_console_input).before(input::update_action_state));
+
+        #[cfg(feature = "dev-tools")]
+        app.add_plugins(bevy_inspector_egui::quick::WorldInspectorPlugin::new())
+            .register_type::<ShootTimer>()
+            .register_type::<BufferedFire>()
+            .register_type::<EnemyMovement>()
+            .register_type::<GameOver>()
+            .register_type::<WaveWon>()
+            .register_type::<Score>()
+            .register_type::<EnemyShootTimer>()
+            .register_type::<PlayerLives>()
+            .register_type::<Level>()
+            .register_type::<EnemySpeed>()
+            .register_type::<DiveTimer>()
+            .register_type::<ChargeShot>()
+            .register_type::<RunStats>()
+            .register_type::<Bombs>()
+            .register_type::<Heat>();
+
+        #[cfg(feature = "crt")]
+        app.add_plugins(crt::CrtPlugin)
+            .add_systems(Update, apply_crt_setting.in_set(GameplaySet::Ui));
+    }
+}
+
+// === SETUP SYSTEMS ===
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+    // Kept at the origin so panned sounds only depend on play_panned_sound, not on camera moves
+    commands.spawn((SpatialListener::new(STEREO_EAR_GAP), TransformBundle::default()));
+}
+
+// Plays a one-shot effect panned towards where it happened; the window edges are hard left and right
+fn play_panned_sound(commands: &mut Commands, source: Handle<AudioSource>, x: f32, window: &Window) {
+    let balance = (x / (window.width() / 2.0)).clamp(-1.0, 1.0);
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_spatial(true),
+        },
+        TransformBundle::from_transform(Transform::from_xyz(balance * STEREO_EAR_GAP / 2.0, 0.0, 0.0)),
+    ));
+}
+
+#[cfg(feature = "crt")]
+fn apply_crt_setting(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    camera_query: Query<Entity, With<Camera2d>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for camera in camera_query.iter() {
+        if settings.crt {
+            commands.entity(camera).insert(crt::CrtSettings::default());
+        } else {
+            commands.entity(camera).remove::<crt::CrtSettings>();
+        }
+    }
+}
+
+fn spawn_player_ship(commands: &mut Commands, assets: &GameAssets, weapon_tier: u8, flip: GravityFlip) {
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(atlas::SpriteKind::Player),
+            transform: Transform::from_xyz(0.0, flip.y(PLAYER_Y), 0.0),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(50.0, 20.0)),
+                ..default()
+            },
+            ..default()
+        },
+        Player,
+        Velocity(Vec2::ZERO),
+        WeaponState { tier: weapon_tier },
+        FlashOnHit { tint: Color::rgb(2.5, 0.4, 0.4) },
+        WaveScoped,
+    ));
+}
+impl BossPhase {
+    // Spread volleys above 66% health, summoning minions down to 33%, then a desperate laser sweep
+    fn for_health(fraction: f32) -> Self {
+        if fraction > 0.66 {
+            BossPhase::Volley
+        } else if fraction > 0.33 {
+            BossPhase::Summon
+        } else {
+            BossPhase::Desperation
+        }
+    }
+
+    fn attack_interval(self) -> f32 {
+        match self {
+            BossPhase::Volley => 1.2,
+            BossPhase::Summon => 4.0,
+            BossPhase::Desperation => 0.08,
+        }
+    }
+}
+
+fn is_boss_level(ruleset: Ruleset, level: u32) -> bool {
+    ruleset.bosses() && level.is_multiple_of(BOSS_LEVEL_INTERVAL)
+}
+
+fn spawn_boss(commands: &mut Commands, assets: &GameAssets, flip: GravityFlip) {
+    let phase = BossPhase::Volley;
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(atlas::SpriteKind::Enemy),
+            transform: Transform::from_xyz(0.0, flip.y(BOSS_Y), 0.0),
+            sprite: Sprite {
+                color: Color::rgb(1.0, 0.3, 0.6),
+                custom_size: Some(BOSS_SIZE),
+                ..default()
+            },
+            ..default()
+        },
+        Enemy,
+        Boss {
+            max_health: BOSS_HEALTH,
+            attack: Timer::from_seconds(phase.attack_interval(), TimerMode::Repeating),
+            elapsed: 0.0,
+        },
+        phase,
+        Health(BOSS_HEALTH),
+        FlashOnHit { tint: Color::rgb(4.0, 4.0, 4.0) },
+        WaveScoped,
+    ));
+    println!("A boss appears!");
+}
+
+fn spawn_enemies(commands: &mut Commands, assets: &GameAssets, layout: &LevelLayout, level: u32, flip: GravityFlip) {
+    let spacing = Vec2::new(layout.spacing_x, layout.spacing_y);
+    let width = layout.formation_width(level);
+    let start_x = layout.start_x(width);
+
+    // The slot keeps the enemy kind as its row, which is what fire patterns and armor go by
+    for (col, row, kind) in layout.cells_for_level(level) {
+        let target = Vec2::new(start_x + col as f32 * spacing.x, flip.y(layout.start_y + row as f32 * spacing.y));
+        spawn_formation_enemy(commands, assets, layout, level, FormationSlot { col, row: kind }, width, target, flip);
+    }
+}
+
+fn spawn_formation_row(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    layout: &LevelLayout,
+    level: u32,
+    row: usize,
+    width: usize,
+    start_x: f32,
+    y: f32,
+    flip: GravityFlip,
+) {
+    for col in 0..width {
+        let target = Vec2::new(start_x + col as f32 * layout.spacing_x, y);
+        spawn_formation_enemy(commands, assets, layout, level, FormationSlot { col, row }, width, target, flip);
+    }
+}
+
+fn spawn_formation_enemy(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    layout: &LevelLayout,
+    level: u32,
+    slot: FormationSlot,
+    width: usize,
+    target: Vec2,
+    flip: GravityFlip,
+) {
+    let (health, _) = enemy_armor_for_row(slot.row);
+    // Each half of the formation enters from its own side, back rows last
+    let side = if slot.col < width / 2 { -1.0 } else { 1.0 };
+    let from = Vec2::new(side * 700.0, flip.y(400.0));
+    let enemy = spawn_enemy(commands, assets, slot, health, slot.row + 1 == layout.rows, from, target);
+    if layout.has_regen_shield(slot.row, level) {
+        attach_regen_shield(commands, assets, enemy);
+    }
+}
+
+fn spawn_enemy(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    slot: FormationSlot,
+    health: u32,
+    elite: bool,
+    from: Vec2,
+    target: Vec2,
+) -> Entity {
+    let (_, color) = enemy_armor_for_row(slot.row);
+    let mut enemy = commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(atlas::SpriteKind::Enemy),
+            transform: Transform::from_translation(from.extend(0.0)),
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(40.0, 20.0)),
+                ..default()
+            },
+            ..default()
+        },
+        Enemy,
+        slot,
+        FirePattern::for_row(slot.row),
+        ProjectileKind::for_row(slot.row),
+        Health(health),
+        FlashOnHit { tint: Color::rgb(4.0, 4.0, 4.0) },
+        FlyingIn {
+            from,
+            target,
+            t: -(slot.row as f32 * FLY_IN_STAGGER),
+        },
+        WaveScoped,
+    ));
+    if elite {
+        enemy.insert((Elite, Evasive::default()));
+    }
+    enemy.id()
+}
+
+// The ring is its own sprite so it can be hidden while the shield is down
+fn attach_regen_shield(commands: &mut Commands, assets: &GameAssets, enemy: Entity) {
+    let ring = commands
+        .spawn(SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(atlas::SpriteKind::Ring),
+            sprite: Sprite {
+                color: Color::rgba(0.3, 0.6, 1.0, 0.9),
+                custom_size: Some(Vec2::new(54.0, 34.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.5),
+            ..default()
+        })
+        .id();
+    commands.entity(enemy).add_child(ring).insert(RegenShield {
+        up: true,
+        regen: Timer::from_seconds(REGEN_SHIELD_SECONDS, TimerMode::Once),
+        ring,
+    });
+}
+
+// Rebuilt whenever the level changes so later levels get a denser, faster sky in the level's theme
+fn spawn_starfield(
+    mut commands: Commands,
+    level: Res<Level>,
+    layout: Res<LevelLayout>,
+    windows: Query<&Window>,
+    background_query: Query<Entity, Or<(With<Star>, With<Backdrop>)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !level.is_changed() {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    for entity in background_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let theme = layout.theme_for_level(level.0);
+    let (nebula_r, nebula_g, nebula_b, nebula_a) = theme.nebula;
+    if nebula_a > 0.0 {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(nebula_r, nebula_g, nebula_b, nebula_a),
+                    custom_size: Some(Vec2::new(window.width(), window.height())),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, -20.0),
+                ..default()
+            },
+            Backdrop,
+        ));
+    }
+    if let Some(planet) = &theme.planet {
+        let (r, g, b) = planet.color;
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Circle::new(planet.radius))),
+                material: materials.add(Color::rgb(r, g, b)),
+                transform: Transform::from_xyz(planet.position.0, planet.position.1, -15.0),
+                ..default()
+            },
+            Backdrop,
+        ));
+    }
+
+    let mut rng = rand::rng();
+    let half_width = window.width() / 2.0;
+    let half_height = window.height() / 2.0;
+    let progression = 1.0 + (level.0.saturating_sub(1)) as f32 * 0.15;
+    let (star_r, star_g, star_b) = theme.star_color;
+    for (depth, (count, speed, size)) in STAR_LAYERS.iter().enumerate() {
+        let brightness = 0.4 + depth as f32 * 0.25;
+        for _ in 0..(*count as f32 * progression * theme.star_density) as usize {
+            let x = rng.random_range(-half_width..half_width);
+            let y = rng.random_range(-half_height..half_height);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(brightness * star_r, brightness * star_g, brightness * star_b),
+                        custom_size: Some(Vec2::splat(*size)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(x, y, -10.0 + depth as f32),
+                    ..default()
+                },
+                Star { speed: speed * progression },
+            ));
+        }
+    }
+}
+
+fn scroll_starfield(
+    time: Res<Time>,
+    windows: Query<&Window>,
+    flip: Res<GravityFlip>,
+    mut star_query: Query<(&mut Transform, &Star)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let half_height = window.height() / 2.0;
+    // Stars stream past the ship from the direction it is flying
+    for (mut transform, star) in star_query.iter_mut() {
+        transform.translation.y -= flip.y(star.speed * time.delta_seconds());
+        if flip.y(transform.translation.y) < -half_height {
+            transform.translation.y += flip.y(window.height());
+        }
+    }
+}
+
+// === GAME LOGIC SYSTEMS ===
+fn player_movement(
+    action_state: Res<ActionState>,
+    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
+    windows: Query<&Window>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    bounds: Res<PlayfieldBounds>,
+) {
+    let speed = config.player_speed;
+    let window = windows.single();
+    let half_width = window.width() / 2.0;
+    let player_half_width = 25.0; // Half of player width (50.0 / 2)
+    let max_step = speed * time.delta_seconds();
+
+    for (mut transform, mut velocity) in query.iter_mut() {
+        let mut direction: f32 = 0.0;
+        if settings.controls == ControlScheme::Mouse {
+            // Chase the pointer no faster than the keyboard would move the ship
+            if let Some(cursor) = window.cursor_position() {
+                let target_x = cursor.x - half_width;
+                if max_step > 0.0 {
+                    direction = ((target_x - transform.translation.x) / max_step).clamp(-1.0, 1.0);
+                }
+            }
+        } else {
+            if action_state.pressed(PlayerAction::MoveLeft) {
+                direction -= 1.0;
+            }
+            if action_state.pressed(PlayerAction::MoveRight) {
+                direction += 1.0;
+            }
+        }
+
+        match settings.movement {
+            MovementModel::Classic => {
+                velocity.0.x = 0.0;
+                transform.translation.x += direction * max_step;
+            }
+            MovementModel::Inertia => {
+                let target_speed = direction * speed;
+                let rate = if direction != 0.0 { config.player_acceleration } else { config.player_friction };
+                let max_change = rate * time.delta_seconds();
+                velocity.0.x += (target_speed - velocity.0.x).clamp(-max_change, max_change);
+                transform.translation.x += velocity.0.x * time.delta_seconds();
+            }
+        }
+
+        if bounds.wrap {
+            // Once the ship is fully off one edge it reappears just outside the other
+            let wrap_edge = half_width + player_half_width;
+            if transform.translation.x > wrap_edge {
+                transform.translation.x -= wrap_edge * 2.0;
+            } else if transform.translation.x < -wrap_edge {
+                transform.translation.x += wrap_edge * 2.0;
+            }
+            continue;
+        }
+
+        // Clamp player position to stay within the screen bounds
+        let clamped_x = transform.translation.x
+            .clamp(-half_width + player_half_width, half_width - player_half_width);
+        if clamped_x != transform.translation.x {
+            // Bumping into the edge kills the momentum
+            velocity.0.x = 0.0;
+        }
+        transform.translation.x = clamped_x;
+    }
+}
+
+fn fire_bullet(
+    action_state: Res<ActionState>,
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shoot_timer: ResMut<ShootTimer>,
+    mut buffered_fire: ResMut<BufferedFire>,
+    mut charge: ResMut<ChargeShot>,
+    query: Query<(&Transform, &WeaponState, Has<SpreadShot>, Has<PiercingRounds>), (With<Player>, Without<LaserWeapon>)>,
+    settings: Res<Settings>,
+    mut heat: ResMut<Heat>,
+    // Bundled to stay within Bevy's parameter limit
+    (palette, flip, ruleset): (Res<Palette>, Res<GravityFlip>, Res<Ruleset>),
+    config: Res<GameConfig>,
+    mut stats: ResMut<RunStats>,
+    mut fired_events: EventWriter<ShotFired>,
+    mut rumble_events: EventWriter<rumble::Rumble>,
+    mut auto_fire_on: Local<bool>,
+    bullet_query: Query<(), With<Bullet>>,
+) {
+    let Ok((player_tf, weapon, spread, piercing_rounds)) = query.get_single() else {
+        shoot_timer.0.tick(time.delta());
+        buffered_fire.0 = 0.0;
+        *auto_fire_on = false;
+        charge.held = 0.0;
+        return;
+    };
+    let cooldown = if weapon.tier >= 3 { config.player_shoot_cooldown * 0.5 } else { config.player_shoot_cooldown };
+    shoot_timer.0.set_duration(std::time::Duration::from_secs_f32(cooldown));
+    shoot_timer.0.tick(time.delta());
+
+    let overheated = settings.overheat && heat.lockout.is_some();
+    let bullet_in_flight = (settings.one_bullet || *ruleset == Ruleset::Classic) && !bullet_query.is_empty();
+
+    // A tap fires a normal shot; keeping Space held charges a piercing shot released on key-up.
+    // Taps landing just before the cooldown ends are queued and fire the moment it does.
+    if action_state.just_pressed(PlayerAction::Fire) {
+        buffered_fire.0 = FIRE_BUFFER_SECONDS;
+        if settings.fire_mode == FireMode::Toggle {
+            *auto_fire_on = !*auto_fire_on;
+        }
+    }
+    let wants_shot = match settings.fire_mode {
+        FireMode::Tap => buffered_fire.0 > 0.0,
+        FireMode::Hold => action_state.pressed(PlayerAction::Fire) || buffered_fire.0 > 0.0,
+        FireMode::Toggle => *auto_fire_on,
+    };
+    if wants_shot && shoot_timer.0.finished() && !overheated && !bullet_in_flight {
+        buffered_fire.0 = 0.0;
+        let offsets: &[f32] = if weapon.tier >= 2 { &[-12.0, 12.0] } else { &[0.0] };
+        let angles: &[f32] = if spread { &[-PLAYER_SPREAD_ANGLE, 0.0, PLAYER_SPREAD_ANGLE] } else { &[0.0] };
+        for (offset, angle) in offsets.iter().flat_map(|offset| angles.iter().map(move |angle| (offset, angle))) {
+            let bullet_spawn = player_tf.translation + Vec3::new(*offset, flip.y(20.0), 0.0);
+            let velocity = Vec2::from_angle(*angle).rotate(Vec2::Y * flip.y(config.bullet_speed));
+            let mut bullet = commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: palette.player_bullet,
+                        custom_size: Some(Vec2::new(5.0, 15.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(bullet_spawn)
+                        .with_rotation(Quat::from_rotation_z(*angle)),
+                    ..default()
+                },
+                Bullet,
+                Velocity(velocity),
+                DespawnOutside(OFFSCREEN_MARGIN),
+                WaveScoped,
+            ));
+            if piercing_rounds {
+                bullet.insert(Pierce(PIERCE_KILLS));
+            }
+            stats.shots_fired += 1;
+            fired_events.send(ShotFired);
+        }
+        shoot_timer.0.reset();
+        if settings.overheat {
+            heat.level += HEAT_PER_SHOT;
+        }
+    }
+    buffered_fire.0 = (buffered_fire.0 - time.delta_seconds()).max(0.0);
+
+    // Holding only charges when it isn't already auto-firing
+    if settings.fire_mode != FireMode::Tap {
+        charge.held = 0.0;
+    } else if action_state.pressed(PlayerAction::Fire) {
+        charge.held += time.delta_seconds();
+    } else if action_state.just_released(PlayerAction::Fire) {
+        if charge.held >= config.charge_shot_time && !overheated && !bullet_in_flight {
+            let bullet_spawn = player_tf.translation + Vec3::Y * flip.y(30.0);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: palette.charged_bullet,
+                        custom_size: Some(Vec2::new(12.0, 30.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(bullet_spawn),
+                    ..default()
+                },
+                Bullet,
+                Velocity(Vec2::Y * flip.y(config.bullet_speed)),
+                Piercing::default(),
+                DespawnOutside(OFFSCREEN_MARGIN),
+                WaveScoped,
+            ));
+            stats.shots_fired += 1;
+            fired_events.send(ShotFired);
+            rumble_events.send(rumble::Rumble { strength: 0.4, seconds: 0.15 });
+            if settings.overheat {
+                heat.level += HEAT_PER_CHARGE_SHOT;
+            }
+        }
+        charge.held = 0.0;
+    }
+}
+
+// Holding Space with a laser active keeps a beam up to the first enemy above the ship
+fn fire_laser(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &Transform, &mut LaserWeapon), With<Player>>,
+    enemy_query: Query<(Entity, &Transform, &Sprite), (With<Enemy>, Without<LaserBeam>)>,
+    mut beam_query: Query<(&mut Transform, &mut Sprite), (With<LaserBeam>, Without<Player>, Without<Enemy>)>,
+    beam_entities: Query<Entity, With<LaserBeam>>,
+    windows: Query<&Window>,
+    palette: Res<Palette>,
+    flip: Res<GravityFlip>,
+    mut hit_events: EventWriter<EnemyHit>,
+) {
+    let Ok((player_entity, player_tf, mut laser)) = player_query.get_single_mut() else {
+        for entity in beam_entities.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+    laser.remaining.tick(time.delta());
+    if laser.remaining.finished() || !action_state.pressed(PlayerAction::Fire) {
+        for entity in beam_entities.iter() {
+            commands.entity(entity).despawn();
+        }
+        if laser.remaining.finished() {
+            commands.entity(player_entity).remove::<LaserWeapon>();
+            println!("Laser depleted");
+        }
+        return;
+    }
+
+    let origin = player_tf.translation + Vec3::Y * flip.y(20.0);
+    let top = flip.y(windows.get_single().map(|window| window.height() / 2.0).unwrap_or(300.0));
+    // The beam stops at the nearest enemy overlapping the ship's column
+    let target = enemy_query
+        .iter()
+        .filter(|(_, tf, sprite)| {
+            let half_width = sprite.custom_size.unwrap_or(Vec2::ZERO).x / 2.0 + LASER_WIDTH / 2.0;
+            flip.y(tf.translation.y) > flip.y(origin.y) && (tf.translation.x - origin.x).abs() < half_width
+        })
+        .min_by(|a, b| flip.y(a.1.translation.y).total_cmp(&flip.y(b.1.translation.y)));
+    let end = target.map(|(_, tf, _)| tf.translation.y).unwrap_or(top);
+    let length = flip.y(end - origin.y).max(0.0);
+    let center = Vec3::new(origin.x, origin.y + flip.y(length / 2.0), origin.z - 0.1);
+    let size = Vec2::new(LASER_WIDTH, length);
+
+    match beam_query.get_single_mut() {
+        Ok((mut transform, mut sprite)) => {
+            transform.translation = center;
+            sprite.custom_size = Some(size);
+            sprite.color = palette.laser;
+        }
+        Err(_) => {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: palette.laser,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(center),
+                    ..default()
+                },
+                LaserBeam,
+                WaveScoped,
+            ));
+        }
+    }
+
+    laser.tick.tick(time.delta());
+    if laser.tick.just_finished()
+        && let Some((enemy, _, _)) = target
+    {
+        hit_events.send(EnemyHit { enemy, damage: 1, charged: false, through_shield: false });
+    }
+}
+
+fn spawn_bullet_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    bullet_query: Query<(&Transform, &Sprite, Option<&Piercing>), With<Bullet>>,
+    palette: Res<Palette>,
+) {
+    // Nothing moves while the world is frozen, so don't stack segments in place
+    if time.delta_seconds() == 0.0 {
+        return;
+    }
+    for (transform, sprite, piercing) in bullet_query.iter() {
+        // Charged shots leave a brighter, wider streak than regular bullets
+        let (color, alpha) = if piercing.is_some() {
+            (palette.charged_bullet, 0.7)
+        } else {
+            (palette.player_bullet, 0.3)
+        };
+        let size = sprite.custom_size.unwrap_or(Vec2::ONE) * Vec2::new(0.6, 1.0);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: color.with_a(alpha),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(transform.translation - Vec3::Z * 0.1),
+                ..default()
+            },
+            TrailSegment { alpha },
+            Lifetime(Timer::from_seconds(TRAIL_LIFETIME, TimerMode::Once)),
+        ));
+    }
+}
+
+fn fade_bullet_trails(mut query: Query<(&mut Sprite, &mut Transform, &TrailSegment, &Lifetime)>) {
+    for (mut sprite, mut transform, segment, lifetime) in query.iter_mut() {
+        let remaining = lifetime.0.fraction_remaining();
+        sprite.color.set_a(segment.alpha * remaining);
+        transform.scale.x = remaining;
+    }
+}
+
+fn update_score_popups(
+    time: Res<Time>,
+    mut query: Query<(&mut Text, &mut Transform, &Lifetime), With<ScorePopup>>,
+) {
+    for (mut text, mut transform, lifetime) in query.iter_mut() {
+        transform.translation.y += SCORE_POPUP_RISE * time.delta_seconds();
+        let remaining = lifetime.0.fraction_remaining();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(remaining);
+        }
+    }
+}
+
+fn expire_lifetimes(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Lifetime)>) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+impl DespawnOutside {
+    fn is_outside(&self, position: Vec3, window: &Window) -> bool {
+        position.x.abs() > window.width() / 2.0 + self.0 || position.y.abs() > window.height() / 2.0 + self.0
+    }
+}
+
+fn despawn_outside_window(
+    mut commands: Commands,
+    windows: Query<&Window>,
+    query: Query<(Entity, &Transform, &DespawnOutside)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    for (entity, transform, bounds) in query.iter() {
+        if bounds.is_outside(transform.translation, window) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn cool_weapon(time: Res<Time>, settings: Res<Settings>, mut heat: ResMut<Heat>) {
+    if !settings.overheat {
+        heat.level = 0.0;
+        heat.lockout = None;
+        return;
+    }
+    if let Some(lockout) = heat.lockout.as_mut() {
+        lockout.tick(time.delta());
+        if lockout.finished() {
+            heat.lockout = None;
+            heat.level = 0.0;
+        }
+        return;
+    }
+    heat.level = (heat.level - HEAT_COOL_RATE * time.delta_seconds()).max(0.0);
+    if heat.level >= 1.0 {
+        heat.level = 1.0;
+        heat.lockout = Some(Timer::from_seconds(OVERHEAT_LOCKOUT, TimerMode::Once));
+        println!("Weapon overheated!");
+    }
+}
+
+fn bullet_movement(
+    mut query: Query<(&mut Transform, &Velocity, &DespawnOutside, Option<&Piercing>, Option<&Pierce>), With<Bullet>>,
+    windows: Query<&Window>,
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    mut missed_events: EventWriter<ShotMissed>,
+) {
+    let window = windows.get_single().ok();
+    for (mut transform, velocity, bounds, piercing, pierce) in query.iter_mut() {
+        transform.translation += velocity.0.extend(0.0) * time.delta_seconds();
+        // despawn_outside_window removes it later this frame; this only records the miss
+        if window.is_some_and(|window| bounds.is_outside(transform.translation, window)) {
+            // A shot that leaves the screen without hitting anything breaks the combo
+            let hit_something = piercing.is_some_and(|piercing| !piercing.hit.is_empty())
+                || pierce.is_some_and(|pierce| pierce.0 < PIERCE_KILLS);
+            if !hit_something {
+                stats.combo = 0;
+                missed_events.send(ShotMissed);
+            }
+        }
+    }
+}
+
+fn enemy_movement(
+    mut movement: ResMut<EnemyMovement>,
+    time: Res<Time>,
+    windows: Query<&Window>,
+    mut query: Query<&mut Transform, (With<Enemy>, Without<Diving>, Without<Boss>)>,
+    flying_query: Query<(), With<FlyingIn>>,
+    enemy_speed: Res<EnemySpeed>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    ruleset: Res<Ruleset>,
+    progress: Res<WaveProgress>,
+    flip: Res<GravityFlip>,
+) {
+    // Hold the march until the whole wave has reached its slots
+    if !flying_query.is_empty() {
+        return;
+    }
+    let mut speed = enemy_speed.0 * difficulty.enemy_speed_scale();
+    if *ruleset == Ruleset::Classic && progress.total > 0 {
+        // Same curve every level: base speed, climbing as invaders fall until the last one runs at full tilt
+        let cleared = 1.0 - progress.remaining as f32 / progress.total as f32;
+        speed = config.enemy_speed * difficulty.enemy_speed_scale() * (1.0 + CLASSIC_SPEEDUP * cleared);
+    }
+    let window = windows.single();
+    let half_width = window.width() / 2.0;
+
+    // Bounce on the live extent of the formation, so clearing an edge column frees up travel room
+    let Some((min_x, max_x)) = query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .fold(None, |bounds, x| match bounds {
+            None => Some((x, x)),
+            Some((min_x, max_x)) => Some((f32::min(min_x, x), f32::max(max_x, x))),
+        })
+    else {
+        return;
+    };
+    let step = movement.direction * speed * time.delta_seconds();
+    let need_step_down = (movement.direction > 0.0 && max_x + step > half_width - ENEMY_HALF_WIDTH)
+        || (movement.direction < 0.0 && min_x + step < -half_width + ENEMY_HALF_WIDTH);
+    if need_step_down {
+        movement.direction *= -1.0;
+    }
+
+    for mut transform in query.iter_mut() {
+        if need_step_down {
+            // Only step down once per direction change (use timer to limit how often this happens if needed)
+            transform.translation.y -= flip.y(config.enemy_step_down);
+        } else {
+            // Smooth horizontal movement
+            transform.translation.x += step;
+        }
+    }
+    if need_step_down {
+        movement.drift.y -= flip.y(config.enemy_step_down);
+    } else {
+        movement.drift.x += step;
+    }
+}
+
+fn evade_player_bullets(
+    time: Res<Time>,
+    layout: Res<LevelLayout>,
+    flip: Res<GravityFlip>,
+    bullet_query: Query<(&Transform, &Velocity), With<Bullet>>,
+    mut enemy_query: Query<(&mut Transform, &Sprite, &mut Evasive), (With<Enemy>, Without<Bullet>, Without<Diving>, Without<FlyingIn>)>,
+) {
+    let half_cell = layout.spacing_x / 2.0;
+    let dt = time.delta_seconds();
+    for (mut transform, sprite, mut evasive) in enemy_query.iter_mut() {
+        evasive.cooldown.tick(time.delta());
+        if evasive.cooldown.finished() {
+            let half_width = sprite.custom_size.unwrap_or(Vec2::ZERO).x / 2.0;
+            let threat = bullet_query.iter().find_map(|(bullet_tf, velocity)| {
+                if flip.y(velocity.0.y) <= 0.0 {
+                    return None;
+                }
+                // Where the bullet will be when it reaches this enemy's height
+                let arrival = (transform.translation.y - bullet_tf.translation.y) / velocity.0.y;
+                let x = bullet_tf.translation.x + velocity.0.x * arrival;
+                ((0.0..DODGE_LOOKAHEAD).contains(&arrival) && (x - transform.translation.x).abs() < half_width).then_some(x)
+            });
+            if let Some(threat_x) = threat {
+                // Step away from the bullet's path, or the other way if that would leave the slot's bounds
+                let away = if threat_x > transform.translation.x { -1.0 } else { 1.0 };
+                let mut target = evasive.offset + away * half_cell;
+                if target.abs() > half_cell {
+                    target = evasive.offset - away * half_cell;
+                }
+                evasive.target = target.clamp(-half_cell, half_cell);
+                evasive.cooldown = Timer::from_seconds(DODGE_COOLDOWN, TimerMode::Once);
+            }
+        }
+        let step = (evasive.target - evasive.offset).clamp(-DODGE_SPEED * dt, DODGE_SPEED * dt);
+        evasive.offset += step;
+        transform.translation.x += step;
+    }
+}
+
+fn check_game_over(
+    mut game_over: ResMut<GameOver>,
+    flip: Res<GravityFlip>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Diving>)>,
+    mut lost_events: EventReader<FriendlyLost>,
+) {
+    if lost_events.read().count() > 0 {
+        game_over.0 = true;
+        println!("Game Over! The cargo ship was destroyed.");
+        return;
+    }
+    for transform in enemy_query.iter() {
+        if flip.y(transform.translation.y) <= -250.0 {
+            game_over.0 = true;
+            println!("Game Over!");
+            break;
+        }
+    }
+}
+
+// Run condition that freezes gameplay while the game over (or wave won) screen is up
+fn game_over(game_over: Res<GameOver>) -> bool {
+    game_over.0
+}
+
+fn check_win_condition(
+    enemy_query: Query<&Transform, With<Enemy>>,
+    game_over: Res<GameOver>,
+    mut kill_cam: ResMut<KillCam>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut cleared_events: EventWriter<WaveCleared>,
+) {
+    if let Some(transform) = enemy_query.iter().next() {
+        // Once the wave is down to one, this is where it goes out
+        kill_cam.focus = transform.translation.truncate();
+        kill_cam.active = false;
+        return;
+    }
+    if game_over.0 {
+        return;
+    }
+    if !kill_cam.active {
+        kill_cam.active = true;
+        kill_cam.timer = Timer::from_seconds(KILL_CAM_SECONDS, TimerMode::Once);
+        slow_motion.start(KILL_CAM_TIME_SCALE, KILL_CAM_SECONDS);
+    } else if kill_cam.timer.finished() {
+        cleared_events.send(WaveCleared);
+    }
+}
+
+// Quick push in on the final kill that holds until the summary; otherwise keeps the camera at rest
+fn update_kill_cam(
+    time: Res<Time<Real>>,
+    mut kill_cam: ResMut<KillCam>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let zoom = if kill_cam.active && !kill_cam.timer.finished() {
+        kill_cam.timer.tick(time.delta());
+        (kill_cam.timer.fraction() * 3.0).min(1.0)
+    } else {
+        0.0
+    };
+    let scale = 1.0 - KILL_CAM_ZOOM * zoom;
+    let position = kill_cam.focus * zoom;
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        if projection.scale != scale {
+            projection.scale = scale;
+        }
+        if transform.translation.truncate() != position {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
+
+fn tick_wave_clock(time: Res<Time>, level: Res<Level>, mut clock: ResMut<WaveClock>) {
+    if level.is_changed() {
+        *clock = WaveClock::default();
+    }
+    clock.elapsed += time.delta_seconds();
+}
+
+// Plays the level's reinforcement timeline against the wave clock. In survival the timeline is
+// generated instead: a row every survival::row_interval seconds, or at once when the board is empty
+fn run_wave_director(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    layout: Res<LevelLayout>,
+    level: Res<Level>,
+    clock: Res<WaveClock>,
+    mut director: ResMut<WaveDirector>,
+    enemy_query: Query<(&Transform, &FormationSlot), (With<Enemy>, Without<Diving>, Without<FlyingIn>)>,
+    all_enemies: Query<(), With<Enemy>>,
+    ruleset: Res<Ruleset>,
+    mode: Res<GameMode>,
+    flip: Res<GravityFlip>,
+) {
+    if level.is_changed() {
+        *director = WaveDirector {
+            next_row_at: survival::row_interval(0.0),
+            ..default()
+        };
+    }
+    if is_boss_level(*ruleset, level.0) {
+        return;
+    }
+    loop {
+        let reinforcement = if *mode == GameMode::Survival {
+            let alive = all_enemies.iter().count();
+            if (clock.elapsed < director.next_row_at && alive > 0) || alive >= survival::MAX_ENEMIES {
+                break;
+            }
+            director.next_row_at = clock.elapsed + survival::row_interval(clock.elapsed);
+            Reinforcement { at: clock.elapsed, row: director.next % layout.rows }
+        } else {
+            match layout.reinforcements.get(director.next) {
+                Some(reinforcement) if reinforcement.at <= clock.elapsed => reinforcement.clone(),
+                _ => break,
+            }
+        };
+        director.next += 1;
+        // Line up with the formation's current sideways position, one row behind its back row
+        let width = layout.formation_width(level.0);
+        let start_x = layout.start_x(width);
+        let offset = enemy_query
+            .iter()
+            .next()
+            .map_or(0.0, |(transform, slot)| transform.translation.x - (start_x + slot.col as f32 * layout.spacing_x));
+        let top = enemy_query
+            .iter()
+            .map(|(transform, _)| flip.y(transform.translation.y))
+            .reduce(f32::max)
+            .unwrap_or(layout.start_y);
+        let y = flip.y(top + layout.spacing_y);
+        spawn_formation_row(&mut commands, &assets, &layout, level.0, reinforcement.row, width, start_x + offset, y, *flip);
+        println!("Reinforcements at {:.0}s: row {}", reinforcement.at, reinforcement.row);
+        // Generated rows come one per frame, since the queries can't see a row until it is spawned
+        if *mode == GameMode::Survival {
+            break;
+        }
+    }
+}
+
+fn time_bonus(elapsed: f32) -> u32 {
+    let fraction = (1.0 - elapsed / TIME_BONUS_WINDOW).max(0.0);
+    // Rounded to tens so the summary reads cleanly
+    ((fraction * TIME_BONUS_MAX as f32 / 10.0).round() as u32) * 10
+}
+
+fn on_wave_cleared(
+    mut cleared_events: EventReader<WaveCleared>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut stats: ResMut<RunStats>,
+    mut score: ResMut<Score>,
+    mut clock: ResMut<WaveClock>,
+    new_game_plus: Res<new_game_plus::NewGamePlus>,
+) {
+    if cleared_events.read().last().is_some() && !wave_won.0 {
+        wave_won.0 = true;
+        stats.waves_cleared += 1;
+        game_over.0 = true;
+        clock.time_bonus = new_game_plus.prestige.score(time_bonus(clock.elapsed));
+        score.0 += clock.time_bonus;
+        println!("You win! Cleared in {:.1}s, time bonus {}", clock.elapsed, clock.time_bonus);
+    }
+}
+
+fn detect_run_finished(
+    game_over: Res<GameOver>,
+    wave_won: Res<WaveWon>,
+    score: Res<Score>,
+    level: Res<Level>,
+    mut assists: ResMut<RunAssists>,
+    mut reported: Local<bool>,
+    mut finished_events: EventWriter<RunFinished>,
+) {
+    if !game_over.0 {
+        *reported = false;
+        return;
+    }
+    if wave_won.0 || *reported {
+        return;
+    }
+    *reported = true;
+    finished_events.send(RunFinished { score: score.0, level: level.0, reduced_speed: assists.reduced_speed });
+    assists.reduced_speed = false;
+}
+
+fn track_run_assists(settings: Res<Settings>, game_over: Res<GameOver>, mut assists: ResMut<RunAssists>) {
+    if !game_over.0 && settings.game_speed != GameSpeed::Full {
+        assists.reduced_speed = true;
+    }
+}
+
+fn record_high_score(
+    mut finished_events: EventReader<RunFinished>,
+    mut high_scores: ResMut<HighScores>,
+    player_name: Res<PlayerName>,
+    profile: Res<CurrentProfile>,
+    mode: Res<GameMode>,
+) {
+    for run in finished_events.read() {
+        high_scores.insert(HighScoreEntry {
+            name: player_name.0.clone(),
+            score: run.score,
+            level: run.level,
+            mode: mode.name().to_string(),
+            reduced_speed: run.reduced_speed,
+        });
+        high_scores.save(&profile);
+    }
+}
+
+#[cfg(feature = "online-leaderboard")]
+fn submit_online_score(
+    mut submit_events: EventReader<SubmitScore>,
+    mut online: ResMut<OnlineLeaderboard>,
+    player_name: Res<PlayerName>,
+    mode: Res<GameMode>,
+) {
+    for SubmitScore(run) in submit_events.read() {
+        let url = online.url.clone();
+        let name = player_name.0.clone();
+        let score = run.score;
+        let reduced_speed = run.reduced_speed;
+        let mode = mode.name();
+        online.task = Some(bevy::tasks::IoTaskPool::get().spawn(async move {
+            leaderboard::submit_score(&url, &leaderboard::Submission {
+                name: &name,
+                score,
+                mode,
+                reduced_speed,
+                version: env!("CARGO_PKG_VERSION"),
+            })?;
+            leaderboard::fetch_top_scores(&url)
+        }));
+    }
+}
+
+#[cfg(feature = "online-leaderboard")]
+fn fetch_online_leaderboard(screen: Res<LeaderboardScreen>, mut online: ResMut<OnlineLeaderboard>) {
+    if !screen.is_changed() || !screen.open || online.task.is_some() {
+        return;
+    }
+    let url = online.url.clone();
+    online.task = Some(bevy::tasks::IoTaskPool::get().spawn(async move {
+        leaderboard::fetch_top_scores(&url)
+    }));
+}
+
+#[cfg(feature = "online-leaderboard")]
+fn poll_online_leaderboard(mut online: ResMut<OnlineLeaderboard>) {
+    let Some(task) = online.task.as_mut() else {
+        return;
+    };
+    let Some(result) = bevy::tasks::block_on(bevy::tasks::poll_once(task)) else {
+        return;
+    };
+    online.task = None;
+    match result {
+        Ok(scores) => online.scores = Some(scores),
+        Err(error) => {
+            // Fall back to the local table until the server is reachable again
+            warn!("Online leaderboard unavailable: {error}");
+            online.scores = None;
+        }
+    }
+}
+
+// The formation reaching the ship ends the run no matter how many lives are left; divers only cost one
+fn enemy_player_collision(
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &Transform, Has<Diving>), With<Enemy>>,
+    player_query: Query<(Entity, &Transform, &Sprite), With<Player>>,
+    mut player_damaged_events: EventWriter<PlayerDamagedEvent>,
+) {
+    for (enemy_entity, enemy_tf, diving) in enemy_query.iter() {
+        let enemy_pos = enemy_tf.translation;
+        for (player_entity, player_tf, player_sprite) in player_query.iter() {
+            let player_size = player_sprite.custom_size.unwrap_or(Vec2::ZERO);
+            let player_pos = player_tf.translation;
+            let collision = enemy_pos.x < player_pos.x + player_size.x / 2.0
+                && enemy_pos.x > player_pos.x - player_size.x / 2.0
+                && enemy_pos.y < player_pos.y + player_size.y / 2.0
+                && enemy_pos.y > player_pos.y - player_size.y / 2.0;
+            if !collision {
+                continue;
+            }
+            // A diver rams the ship and is gone; it costs a life like any other hit
+            if diving {
+                commands.entity(enemy_entity).despawn_recursive();
+                player_damaged_events.send(PlayerDamagedEvent { player: player_entity, lethal: false });
+                println!("A diver rammed the player!");
+            } else {
+                player_damaged_events.send(PlayerDamagedEvent { player: player_entity, lethal: true });
+                println!("Enemy collided with player!");
+            }
+            return;
+        }
+    }
+}
+
+fn bullet_enemy_collision(
+    mut commands: Commands,
+    mut bullet_query: Query<(Entity, &Transform, Option<&mut Piercing>, Option<&mut Pierce>), With<Bullet>>,
+    enemy_query: Query<(Entity, &Transform, &Sprite, &Health), (With<Enemy>, Without<Bullet>)>,
+    mut hit_events: EventWriter<EnemyHit>,
+    mut stats: ResMut<RunStats>,
+) {
+    for (bullet_entity, bullet_tf, mut piercing, mut pierce) in bullet_query.iter_mut() {
+        let bullet_pos = bullet_tf.translation;
+        for (enemy_entity, enemy_tf, enemy_sprite, health) in enemy_query.iter() {
+            if piercing.as_ref().is_some_and(|piercing| piercing.hit.contains(&enemy_entity)) {
+                continue;
+            }
+            let enemy_size = enemy_sprite.custom_size.unwrap_or(Vec2::ZERO);
+            let enemy_pos = enemy_tf.translation;
+            let collision = bullet_pos.x < enemy_pos.x + enemy_size.x / 2.0
+                && bullet_pos.x > enemy_pos.x - enemy_size.x / 2.0
+                && bullet_pos.y < enemy_pos.y + enemy_size.y / 2.0
+                && bullet_pos.y > enemy_pos.y - enemy_size.y / 2.0;
+            if collision {
+                // Piercing shots keep flying and remember who they already hit
+                match piercing.as_mut() {
+                    Some(piercing) => {
+                        if piercing.hit.is_empty() {
+                            stats.record_hit();
+                        }
+                        piercing.hit.push(enemy_entity);
+                        hit_events.send(EnemyHit { enemy: enemy_entity, damage: CHARGE_SHOT_DAMAGE, charged: true, through_shield: false });
+                    }
+                    None => {
+                        if pierce.as_ref().is_none_or(|pierce| pierce.0 == PIERCE_KILLS) {
+                            stats.record_hit();
+                        }
+                        hit_events.send(EnemyHit { enemy: enemy_entity, damage: 1, charged: false, through_shield: false });
+                        // Piercing rounds carry on through enemies they kill until their kills run out
+                        if let Some(pierce) = pierce.as_mut()
+                            && health.0 <= 1
+                            && pierce.0 > 1
+                        {
+                            pierce.0 -= 1;
+                            continue;
+                        }
+                        commands.entity(bullet_entity).despawn();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// A well-timed shot cancels an incoming enemy bullet; charged shots plough through and keep going
+fn bullet_bullet_collision(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform, &Sprite, Has<Piercing>), With<Bullet>>,
+    enemy_bullet_query: Query<(Entity, &Transform, &Sprite), (With<EnemyBullet>, Without<Bullet>)>,
+    mut score: ResMut<Score>,
+) {
+    let mut cancelled = Vec::new();
+    for (bullet_entity, bullet_tf, bullet_sprite, piercing) in bullet_query.iter() {
+        let bullet_half = bullet_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        for (enemy_bullet_entity, enemy_bullet_tf, enemy_bullet_sprite) in enemy_bullet_query.iter() {
+            if cancelled.contains(&enemy_bullet_entity) {
+                continue;
+            }
+            let enemy_bullet_half = enemy_bullet_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+            let distance = (bullet_tf.translation - enemy_bullet_tf.translation).truncate().abs();
+            let collision = distance.x < bullet_half.x + enemy_bullet_half.x
+                && distance.y < bullet_half.y + enemy_bullet_half.y;
+            if collision {
+                commands.entity(enemy_bullet_entity).despawn();
+                cancelled.push(enemy_bullet_entity);
+                score.0 += BULLET_CANCEL_SCORE;
+                if !piercing {
+                    commands.entity(bullet_entity).despawn();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Rebuilt with every wave (restarts included) so each one starts with intact cover
+fn spawn_bunkers(mut commands: Commands, assets: Res<GameAssets>, palette: Res<Palette>, flip: Res<GravityFlip>, windows: Query<&Window>) {
+    if let Ok(window) = windows.get_single() {
+        build_bunkers(&mut commands, &assets, &palette, window, *flip);
+    }
+}
+
+fn build_bunkers(commands: &mut Commands, assets: &GameAssets, palette: &Palette, window: &Window, flip: GravityFlip) {
+    let gap = window.width() / BUNKER_COUNT as f32;
+    let bunker_width = BUNKER_COLS as f32 * BUNKER_SEGMENT_SIZE;
+    for bunker in 0..BUNKER_COUNT {
+        let left = -window.width() / 2.0 + gap * (bunker as f32 + 0.5) - bunker_width / 2.0;
+        for row in 0..BUNKER_ROWS {
+            for col in 0..BUNKER_COLS {
+                let x = left + (col as f32 + 0.5) * BUNKER_SEGMENT_SIZE;
+                let y = flip.y(BUNKER_Y + row as f32 * BUNKER_SEGMENT_SIZE);
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture: assets.atlas.image.clone(),
+                        atlas: assets.atlas.sprite(atlas::SpriteKind::Bunker),
+                        sprite: Sprite {
+                            color: palette.bunker,
+                            custom_size: Some(Vec2::splat(BUNKER_SEGMENT_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(x, y, 0.0),
+                        ..default()
+                    },
+                    BunkerSegment { hits_left: BUNKER_SEGMENT_HITS },
+                    WaveScoped,
+                ));
+            }
+        }
+    }
+}
+
+// Shots from either side chip away at the cover: first hit cracks a segment, the second removes it
+fn bullet_bunker_collision(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform, &Sprite), Or<(With<Bullet>, With<EnemyBullet>)>>,
+    mut segment_query: Query<
+        (Entity, &Transform, &mut Sprite, &mut TextureAtlas, &mut BunkerSegment),
+        (Without<Bullet>, Without<EnemyBullet>),
+    >,
+    assets: Res<GameAssets>,
+    palette: Res<Palette>,
+) {
+    let half_segment = BUNKER_SEGMENT_SIZE / 2.0;
+    for (bullet_entity, bullet_tf, bullet_sprite) in bullet_query.iter() {
+        let bullet_half = bullet_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        for (segment_entity, segment_tf, mut segment_sprite, mut segment_atlas, mut segment) in segment_query.iter_mut() {
+            if segment.hits_left == 0 {
+                continue;
+            }
+            let distance = (bullet_tf.translation - segment_tf.translation).truncate().abs();
+            if distance.x < bullet_half.x + half_segment && distance.y < bullet_half.y + half_segment {
+                commands.entity(bullet_entity).despawn();
+                segment.hits_left -= 1;
+                if segment.hits_left == 0 {
+                    commands.entity(segment_entity).despawn();
+                } else {
+                    *segment_atlas = assets.atlas.sprite(atlas::SpriteKind::BunkerDamaged);
+                    segment_sprite.color = palette.bunker_damaged;
+                }
+                break;
+            }
+        }
+    }
+}
+
+fn spawn_asteroids(
+    mut commands: Commands,
+    time: Res<Time>,
+    level: Res<Level>,
+    palette: Res<Palette>,
+    flip: Res<GravityFlip>,
+    windows: Query<&Window>,
+    mut timer: ResMut<AsteroidTimer>,
+    mut rng: ResMut<GameRng>,
+) {
+    if level.0 < ASTEROID_MIN_LEVEL || !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    spawn_asteroid(&mut commands, &palette, window, *flip, &mut rng);
+}
+
+// One rock from a random spot beyond the invaders' edge
+fn spawn_asteroid(commands: &mut Commands, palette: &Palette, window: &Window, flip: GravityFlip, rng: &mut GameRng) {
+    let half_width = window.width() / 2.0;
+    let size = rng.0.random_range(20.0..40.0);
+    let x = rng.0.random_range(-half_width..half_width);
+    let drift = rng.0.random_range(-30.0..30.0);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: palette.asteroid,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, flip.y(window.height() / 2.0 + size), 0.0),
+            ..default()
+        },
+        Asteroid { spin: rng.0.random_range(-2.0..2.0) },
+        Velocity(Vec2::new(drift, flip.y(-ASTEROID_SPEED))),
+        DespawnOutside(size + OFFSCREEN_MARGIN),
+        WaveScoped,
+    ));
+}
+
+fn asteroid_movement(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity, &Asteroid)>) {
+    let dt = time.delta_seconds();
+    for (mut transform, velocity, asteroid) in query.iter_mut() {
+        transform.translation += velocity.0.extend(0.0) * dt;
+        transform.rotate_z(asteroid.spin * dt);
+    }
+}
+
+fn boss_movement(time: Res<Time>, mut query: Query<(&mut Transform, &mut Boss)>) {
+    for (mut transform, mut boss) in query.iter_mut() {
+        boss.elapsed += time.delta_seconds();
+        transform.translation.x = (boss.elapsed * BOSS_SWAY_SPEED).sin() * BOSS_SWAY;
+    }
+}
+
+fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<GameAssets>,
+    palette: Res<Palette>,
+    config: Res<GameConfig>,
+    mut boss_query: Query<(&Transform, &mut Boss, &BossPhase)>,
+    minion_query: Query<(), (With<Enemy>, Without<Boss>)>,
+    player_query: Query<&Transform, With<Player>>,
+    flip: Res<GravityFlip>,
+) {
+    let down = Vec2::NEG_Y * flip.up();
+    for (boss_tf, mut boss, phase) in boss_query.iter_mut() {
+        if !boss.attack.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let origin = boss_tf.translation - Vec3::Y * flip.y(BOSS_SIZE.y / 2.0);
+        let speed = config.enemy_bullet_speed;
+        match phase {
+            BossPhase::Volley => {
+                // Fanned around the player so standing still is never safe
+                let aim = player_query
+                    .get_single()
+                    .map(|tf| (tf.translation - origin).truncate().normalize_or_zero())
+                    .ok()
+                    .filter(|direction| *direction != Vec2::ZERO)
+                    .unwrap_or(down);
+                let half = (BOSS_VOLLEY_SHOTS / 2) as f32;
+                for shot in 0..BOSS_VOLLEY_SHOTS {
+                    let angle = (shot as f32 - half) * SPREAD_ANGLE;
+                    spawn_enemy_bullet(&mut commands, &palette, ProjectileKind::Wiggler, origin, Vec2::from_angle(angle).rotate(aim) * speed);
+                }
+            }
+            BossPhase::Summon => {
+                let alive = minion_query.iter().count();
+                for col in alive..BOSS_MINIONS {
+                    let offset = (col as f32 - (BOSS_MINIONS - 1) as f32 / 2.0) * 60.0;
+                    let target = Vec2::new(boss_tf.translation.x + offset, flip.y(BOSS_Y - 80.0));
+                    spawn_enemy(&mut commands, &assets, FormationSlot { col, row: 0 }, 1, false, origin.truncate(), target);
+                }
+                if alive < BOSS_MINIONS {
+                    println!("Boss summons {} minions", BOSS_MINIONS - alive);
+                }
+            }
+            BossPhase::Desperation => {
+                // A fast stream of bolts swinging back and forth like a laser
+                let angle = (boss.elapsed * BOSS_SWEEP_SPEED).sin() * BOSS_SWEEP_ARC;
+                let velocity = Vec2::from_angle(angle).rotate(down) * speed * ProjectileKind::Bolt.speed_scale();
+                spawn_enemy_bullet(&mut commands, &palette, ProjectileKind::Bolt, origin, velocity);
+            }
+        }
+    }
+}
+
+fn update_boss_phase(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &Health, &mut Boss, &mut BossPhase)>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut rumble_events: EventWriter<rumble::Rumble>,
+) {
+    for (entity, health, mut boss, mut phase) in boss_query.iter_mut() {
+        let next = BossPhase::for_health(health.0 as f32 / boss.max_health as f32);
+        if next == *phase || health.0 == 0 {
+            continue;
+        }
+        *phase = next;
+        boss.attack = Timer::from_seconds(next.attack_interval(), TimerMode::Repeating);
+        commands
+            .entity(entity)
+            .insert(Invulnerable(Timer::from_seconds(BOSS_INVULNERABLE_SECONDS, TimerMode::Once)));
+        damaged_events.send(Damaged { entity });
+        rumble_events.send(rumble::Rumble { strength: 0.8, seconds: 0.6 });
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::rgba(1.0, 0.3, 0.6, 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ScreenFlash(Timer::from_seconds(BOMB_FLASH_DURATION, TimerMode::Once)),
+        ));
+        println!("Boss enters phase {:?}", next);
+    }
+}
+
+// Blinks the sprite while hits are ignored
+fn tick_invulnerable(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Sprite, &mut Invulnerable)>,
+) {
+    for (entity, mut sprite, mut invulnerable) in query.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            sprite.color.set_a(1.0);
+            commands.entity(entity).remove::<Invulnerable>();
+        } else {
+            let visible = ((invulnerable.0.elapsed_secs() * 10.0) as u32).is_multiple_of(2);
+            sprite.color.set_a(if visible { 1.0 } else { 0.3 });
+        }
+    }
+}
+
+fn bullet_asteroid_collision(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform, &Sprite), Or<(With<Bullet>, With<EnemyBullet>)>>,
+    asteroid_query: Query<(&Transform, &Sprite), With<Asteroid>>,
+) {
+    for (bullet_entity, bullet_tf, bullet_sprite) in bullet_query.iter() {
+        let bullet_half = bullet_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        for (asteroid_tf, asteroid_sprite) in asteroid_query.iter() {
+            let asteroid_half = asteroid_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+            let distance = (bullet_tf.translation - asteroid_tf.translation).truncate().abs();
+            if distance.x < bullet_half.x + asteroid_half.x && distance.y < bullet_half.y + asteroid_half.y {
+                commands.entity(bullet_entity).despawn();
+                break;
+            }
+        }
+    }
+}
+
+fn asteroid_player_collision(
+    mut commands: Commands,
+    asteroid_query: Query<(Entity, &Transform, &Sprite), With<Asteroid>>,
+    player_query: Query<(Entity, &Transform, &Sprite), With<Player>>,
+    mut player_damaged_events: EventWriter<PlayerDamagedEvent>,
+) {
+    for (player_entity, player_tf, player_sprite) in player_query.iter() {
+        let player_half = player_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        for (asteroid_entity, asteroid_tf, asteroid_sprite) in asteroid_query.iter() {
+            let asteroid_half = asteroid_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+            let distance = (player_tf.translation - asteroid_tf.translation).truncate().abs();
+            if distance.x < player_half.x + asteroid_half.x && distance.y < player_half.y + asteroid_half.y {
+                // The rock breaks up on impact so it can't hit twice
+                commands.entity(asteroid_entity).despawn();
+                player_damaged_events.send(PlayerDamagedEvent { player: player_entity, lethal: false });
+                println!("Asteroid hit the player!");
+                break;
+            }
+        }
+    }
+}
+
+fn apply_enemy_hits(
+    mut commands: Commands,
+    mut hit_events: EventReader<EnemyHit>,
+    mut score: ResMut<Score>,
+    mut enemy_query: Query<(&Transform, &mut Health, Has<Elite>, Has<Invulnerable>, Option<&mut RegenShield>), With<Enemy>>,
+    assets: Res<GameAssets>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut killed_events: EventWriter<EnemyKilled>,
+    palette: Res<Palette>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    windows: Query<&Window>,
+    ruleset: Res<Ruleset>,
+    new_game_plus: Res<new_game_plus::NewGamePlus>,
+) {
+    let window = windows.single();
+    let kill_score = new_game_plus.prestige.score(ENEMY_KILL_SCORE);
+    for hit in hit_events.read() {
+        let Ok((enemy_tf, mut health, elite, invulnerable, regen_shield)) = enemy_query.get_mut(hit.enemy) else {
+            continue;
+        };
+        if health.0 == 0 || invulnerable {
+            continue;
+        }
+        // A raised shield ring soaks up the whole hit, however strong
+        if let Some(mut shield) = regen_shield
+            && shield.up
+            && !hit.through_shield
+        {
+            shield.up = false;
+            shield.regen.reset();
+            commands.entity(shield.ring).insert(Visibility::Hidden);
+            damaged_events.send(Damaged { entity: hit.enemy });
+            play_panned_sound(&mut commands, assets.sounds.clink.clone(), enemy_tf.translation.x, window);
+            continue;
+        }
+        health.0 = health.0.saturating_sub(hit.damage);
+        if health.0 > 0 {
+            // Armored enemy survived: flash it and play a clink instead of despawning
+            damaged_events.send(Damaged { entity: hit.enemy });
+            play_panned_sound(&mut commands, assets.sounds.clink.clone(), enemy_tf.translation.x, window);
+            continue;
+        }
+        commands.entity(hit.enemy).despawn();
+        atlas::spawn_explosion(&mut commands, &assets, enemy_tf.translation);
+        play_panned_sound(&mut commands, assets.sounds.explosion.clone(), enemy_tf.translation.x, window);
+        killed_events.send(EnemyKilled { elite, charged: hit.charged, position: enemy_tf.translation });
+        if ruleset.power_ups() && rng.0.random_bool(config.power_up_drop_chance) {
+            let kind = if rng.0.random_bool(LASER_CHANCE) {
+                PowerUp::Laser
+            } else if rng.0.random_bool(SPREAD_SHOT_CHANCE) {
+                PowerUp::SpreadShot
+            } else if rng.0.random_bool(PIERCE_CHANCE) {
+                PowerUp::Pierce
+            } else {
+                PowerUp::WeaponUpgrade
+            };
+            spawn_power_up(&mut commands, &palette, enemy_tf.translation, kind);
+        }
+        score.0 += kill_score;
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}", kill_score),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_translation(enemy_tf.translation + Vec3::Z),
+                ..default()
+            },
+            ScorePopup,
+            Lifetime(Timer::from_seconds(SCORE_POPUP_LIFETIME, TimerMode::Once)),
+        ));
+        println!("Hit! Score: {}", score.0);
+    }
+}
+
+fn use_smart_bomb(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    game_over: Res<GameOver>,
+    mut bombs: ResMut<Bombs>,
+    enemy_bullet_query: Query<Entity, With<EnemyBullet>>,
+    enemy_query: Query<(Entity, &Transform), (With<Enemy>, Without<Diving>)>,
+    mut hit_events: EventWriter<EnemyHit>,
+    flip: Res<GravityFlip>,
+) {
+    if game_over.0 || bombs.0 == 0 || !action_state.just_pressed(PlayerAction::Bomb) {
+        return;
+    }
+    bombs.0 -= 1;
+
+    for entity in enemy_bullet_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // The row nearest the player, which is the top one on gravity-flip waves
+    let bottom_row = enemy_query
+        .iter()
+        .map(|(_, transform)| flip.y(transform.translation.y))
+        .reduce(f32::min);
+    if let Some(bottom_y) = bottom_row {
+        for (entity, transform) in enemy_query.iter() {
+            if flip.y(transform.translation.y) - bottom_y < 1.0 {
+                hit_events.send(EnemyHit { enemy: entity, damage: BOMB_DAMAGE, charged: false, through_shield: false });
+            }
+        }
+    }
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        ScreenFlash(Timer::from_seconds(BOMB_FLASH_DURATION, TimerMode::Once)),
+    ));
+    println!("Smart bomb! Bombs left: {}", bombs.0);
+}
+
+fn update_screen_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut BackgroundColor, &mut ScreenFlash)>,
+) {
+    for (entity, mut background, mut flash) in query.iter_mut() {
+        flash.0.tick(time.delta());
+        background.0.set_a(0.8 * flash.0.fraction_remaining());
+        if flash.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn start_hit_flash(
+    mut commands: Commands,
+    mut damaged_events: EventReader<Damaged>,
+    mut query: Query<(&mut Sprite, &FlashOnHit, Option<&Flashing>)>,
+) {
+    for damaged in damaged_events.read() {
+        let Ok((mut sprite, flash_on_hit, flashing)) = query.get_mut(damaged.entity) else {
+            continue;
+        };
+        // Keep the colour from before the first flash if hits land back to back
+        let original = flashing.map(|flashing| flashing.original).unwrap_or(sprite.color);
+        sprite.color = flash_on_hit.tint;
+        commands.entity(damaged.entity).insert(Flashing {
+            timer: Timer::from_seconds(HIT_FLASH_DURATION, TimerMode::Once),
+            original,
+        });
+    }
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Sprite, &mut Flashing)>,
+) {
+    for (entity, mut sprite, mut flashing) in query.iter_mut() {
+        flashing.timer.tick(time.delta());
+        if flashing.timer.finished() {
+            sprite.color = flashing.original;
+            commands.entity(entity).remove::<Flashing>();
+        }
+    }
+}
+
+fn spawn_power_up(commands: &mut Commands, palette: &Palette, position: Vec3, kind: PowerUp) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: palette.power_up_color(kind),
+                custom_size: Some(Vec2::new(16.0, 16.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        kind,
+        DespawnOutside(OFFSCREEN_MARGIN),
+        WaveScoped,
+    ));
+}
+
+fn power_up_movement(mut query: Query<&mut Transform, With<PowerUp>>, time: Res<Time>, flip: Res<GravityFlip>) {
+    for mut transform in query.iter_mut() {
+        transform.translation.y -= flip.y(POWER_UP_FALL_SPEED * time.delta_seconds());
+    }
+}
+
+fn expire_power_ups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spread_query: Query<(Entity, &mut SpreadShot)>,
+    mut pierce_query: Query<(Entity, &mut PiercingRounds)>,
+) {
+    for (entity, mut spread) in spread_query.iter_mut() {
+        if spread.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<SpreadShot>();
+            println!("Spread shot expired");
+        }
+    }
+    for (entity, mut pierce) in pierce_query.iter_mut() {
+        if pierce.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<PiercingRounds>();
+            println!("Piercing rounds expired");
+        }
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    power_up_query: Query<(Entity, &Transform, &PowerUp)>,
+    mut player_query: Query<(Entity, &Transform, &Sprite, &mut WeaponState), With<Player>>,
+    mut collected_events: EventWriter<PowerUpCollected>,
+    mut lives: ResMut<PlayerLives>,
+) {
+    let Ok((player_entity, player_tf, player_sprite, mut weapon)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_size = player_sprite.custom_size.unwrap_or(Vec2::ZERO);
+    let player_pos = player_tf.translation;
+    for (entity, power_up_tf, power_up) in power_up_query.iter() {
+        let pos = power_up_tf.translation;
+        let collision = pos.x < player_pos.x + player_size.x / 2.0 + 8.0
+            && pos.x > player_pos.x - player_size.x / 2.0 - 8.0
+            && pos.y < player_pos.y + player_size.y / 2.0 + 8.0
+            && pos.y > player_pos.y - player_size.y / 2.0 - 8.0;
+        if collision {
+            commands.entity(entity).despawn();
+            collected_events.send(PowerUpCollected(*power_up));
+            match power_up {
+                PowerUp::WeaponUpgrade => {
+                    weapon.tier = (weapon.tier + 1).min(MAX_WEAPON_TIER);
+                    println!("Weapon upgraded to tier {}", weapon.tier);
+                }
+                PowerUp::Laser => {
+                    // Picking up another laser simply restarts the timer
+                    commands.entity(player_entity).insert(LaserWeapon {
+                        remaining: Timer::from_seconds(LASER_DURATION, TimerMode::Once),
+                        tick: Timer::from_seconds(LASER_TICK, TimerMode::Repeating),
+                    });
+                    println!("Laser online for {}s", LASER_DURATION);
+                }
+                PowerUp::SpreadShot => {
+                    commands
+                        .entity(player_entity)
+                        .insert(SpreadShot(Timer::from_seconds(SPREAD_SHOT_DURATION, TimerMode::Once)));
+                    println!("Spread shot for {}s", SPREAD_SHOT_DURATION);
+                }
+                PowerUp::Pierce => {
+                    commands
+                        .entity(player_entity)
+                        .insert(PiercingRounds(Timer::from_seconds(PIERCE_DURATION, TimerMode::Once)));
+                    println!("Piercing rounds for {}s", PIERCE_DURATION);
+                }
+                PowerUp::Heal => {
+                    lives.0 += 1;
+                    println!("Extra life, {} left", lives.0);
+                }
+            }
+        }
+    }
+}
+
+fn enemy_fire_bullet(
+    time: Res<Time>,
+    mut shoot_timer: ResMut<EnemyShootTimer>,
+    mut fire_orders: EventWriter<EnemyFireOrder>,
+    difficulty: Res<Difficulty>,
+    level: Res<Level>,
+    config: Res<GameConfig>,
+) {
+    shoot_timer.0.tick(time.delta().mul_f32(difficulty.enemy_fire_rate()));
+    if shoot_timer.0.finished() {
+        // One more invader joins each volley every three levels
+        let shooters = (level.0 / 3 + 1).min(config.max_enemy_shooters);
+        for _ in 0..shooters {
+            fire_orders.send(EnemyFireOrder { column: None });
+        }
+    }
+}
+
+// Carries out firing decisions, whether they came from the AI timer or a versus player
+fn execute_enemy_fire(
+    mut commands: Commands,
+    mut fire_orders: EventReader<EnemyFireOrder>,
+    enemy_query: Query<(&Transform, &FirePattern, &ProjectileKind, &FormationSlot, Has<Elite>), (With<Enemy>, Without<FlyingIn>, Without<Diving>)>,
+    player_query: Query<&Transform, With<Player>>,
+    level: Res<Level>,
+    palette: Res<Palette>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    difficulty: Res<Difficulty>,
+    flip: Res<GravityFlip>,
+) {
+    if fire_orders.is_empty() {
+        return;
+    }
+    // Only the lowest living invader of each column (the highest when flipped) has a clear line of fire.
+    // BTreeMap keeps iteration order stable so seeded runs pick the same shooters.
+    let mut bottom_most: BTreeMap<usize, (&Transform, &FirePattern, &ProjectileKind, bool)> = BTreeMap::new();
+    for (transform, pattern, kind, slot, elite) in enemy_query.iter() {
+        let lowest = bottom_most.entry(slot.col).or_insert((transform, pattern, kind, elite));
+        if flip.y(transform.translation.y) < flip.y(lowest.0.translation.y) {
+            *lowest = (transform, pattern, kind, elite);
+        }
+    }
+
+    for order in fire_orders.read() {
+        // A column fires at most once per volley
+        let column = match order.column {
+            Some(column) => Some(column),
+            None => bottom_most.keys().copied().choose(&mut rng.0),
+        };
+        if let Some((enemy_tf, pattern, kind, elite)) = column.and_then(|column| bottom_most.remove(&column)) {
+            let origin = enemy_tf.translation - Vec3::Y * flip.y(20.0);
+            if elite && rng.0.random_bool(HOMING_CHANCE) {
+                let speed = config.enemy_bullet_speed * ProjectileKind::Homing.speed_scale();
+                spawn_enemy_bullet(&mut commands, &palette, ProjectileKind::Homing, origin, Vec2::NEG_Y * flip.y(speed));
+                continue;
+            }
+            let mut pattern = pattern.unlocked_at(level.0);
+            let mut target = player_query.get_single().map(|tf| tf.translation).ok();
+            // Checked before rolling so easier difficulties don't consume randomness and seeded runs stay put
+            let aim_chance = difficulty.aimed_shot_chance();
+            if pattern == FirePattern::Single && target.is_some() && aim_chance > 0.0 && rng.0.random_bool(aim_chance) {
+                pattern = FirePattern::Aimed;
+                let error = rng.0.random_range(-AIM_ERROR..AIM_ERROR);
+                target = target.map(|target| target + Vec3::X * error);
+            }
+            fire_pattern(&mut commands, &palette, pattern, *kind, origin, target, config.enemy_bullet_speed, *flip);
+        }
+    }
+}
+
+fn fire_pattern(
+    commands: &mut Commands,
+    palette: &Palette,
+    pattern: FirePattern,
+    kind: ProjectileKind,
+    origin: Vec3,
+    target: Option<Vec3>,
+    speed: f32,
+    flip: GravityFlip,
+) {
+    let speed = speed * kind.speed_scale();
+    let straight_down = Vec2::NEG_Y * flip.y(speed);
+    match pattern {
+        FirePattern::Single => spawn_enemy_bullet(commands, palette, kind, origin, straight_down),
+        FirePattern::Spread => {
+            for angle in [-SPREAD_ANGLE, 0.0, SPREAD_ANGLE] {
+                spawn_enemy_bullet(commands, palette, kind, origin, Vec2::from_angle(angle).rotate(straight_down));
+            }
+        }
+        FirePattern::Burst => {
+            // The second round trails the first so both arrive in quick succession
+            spawn_enemy_bullet(commands, palette, kind, origin, straight_down);
+            spawn_enemy_bullet(commands, palette, kind, origin + Vec3::Y * flip.y(BURST_GAP), straight_down);
+        }
+        FirePattern::Aimed => {
+            let direction = target
+                .map(|target| (target - origin).truncate().normalize_or_zero())
+                .filter(|direction| *direction != Vec2::ZERO)
+                .unwrap_or(Vec2::NEG_Y * flip.up());
+            spawn_enemy_bullet(commands, palette, kind, origin, direction * speed);
+        }
+    }
+}
+
+fn spawn_enemy_bullet(commands: &mut Commands, palette: &Palette, kind: ProjectileKind, position: Vec3, velocity: Vec2) {
+    let rotation = palette.enemy_bullet_rotation(velocity);
+    let mut bullet = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: palette.enemy_bullet_color(kind),
+                custom_size: Some(palette.enemy_bullet_size(kind)),
+                ..default()
+            },
+            transform: Transform::from_translation(position).with_rotation(rotation),
+            ..default()
+        },
+        EnemyBullet,
+        Velocity(velocity),
+        kind,
+        ProjectileAge::default(),
+        DespawnOutside(OFFSCREEN_MARGIN),
+        WaveScoped,
+    ));
+    // Homing missiles give up after a while so they can't circle forever
+    if kind == ProjectileKind::Homing {
+        bullet.insert(Lifetime(Timer::from_seconds(HOMING_LIFETIME, TimerMode::Once)));
+    }
+}
+
+fn start_enemy_dive(
+    time: Res<Time>,
+    mut dive_timer: ResMut<DiveTimer>,
+    game_over: Res<GameOver>,
+    mut dive_orders: EventWriter<DiveOrder>,
+) {
+    dive_timer.0.tick(time.delta());
+    if !game_over.0 && dive_timer.0.just_finished() {
+        dive_orders.send(DiveOrder { column: None });
+    }
+}
+
+fn execute_dive(
+    mut commands: Commands,
+    mut dive_orders: EventReader<DiveOrder>,
+    enemy_query: Query<(Entity, &Transform, &FormationSlot), (With<Enemy>, Without<Diving>, Without<FlyingIn>)>,
+    movement: Res<EnemyMovement>,
+    mut rng: ResMut<GameRng>,
+) {
+    for order in dive_orders.read() {
+        let diver = enemy_query
+            .iter()
+            .filter(|(_, _, slot)| order.column.is_none_or(|column| slot.col == column))
+            .choose(&mut rng.0);
+        if let Some((enemy, transform, _)) = diver {
+            let rejoin = rng.0.random_bool(DIVE_REJOIN_CHANCE);
+            let mut entity = commands.entity(enemy);
+            entity.insert(Diving {
+                elapsed: 0.0,
+                swing: if rng.0.random_bool(0.5) { 1.0 } else { -1.0 },
+                shoot_timer: Timer::from_seconds(DIVE_SHOOT_COOLDOWN, TimerMode::Repeating),
+                home: transform.translation.truncate() - movement.drift,
+                rejoin,
+            });
+            // The rest leave the screen for good
+            if !rejoin {
+                entity.insert(DespawnOutside(OFFSCREEN_MARGIN));
+            }
+        }
+    }
+}
+
+fn fly_in_enemies(
+    mut commands: Commands,
+    time: Res<Time>,
+    flip: Res<GravityFlip>,
+    mut query: Query<(Entity, &mut Transform, &mut FlyingIn)>,
+) {
+    for (entity, mut transform, mut flying) in query.iter_mut() {
+        flying.t += time.delta_seconds() / FLY_IN_DURATION;
+        if flying.t >= 1.0 {
+            transform.translation = flying.target.extend(transform.translation.z);
+            commands.entity(entity).remove::<FlyingIn>();
+            continue;
+        }
+        // Quadratic bezier that dips past the slot toward the player before settling, eased in and out
+        let t = flying.t.max(0.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let control = Vec2::new(flying.target.x * 0.3, flying.target.y - flip.y(150.0));
+        let position = flying.from.lerp(control, eased).lerp(control.lerp(flying.target, eased), eased);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn versus_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Versus
+}
+
+// Player two commands the invaders: J/L pick a column, I fires from it and K sends a diver
+fn versus_controls(
+    action_state: Res<ActionState>,
+    time: Res<Time>,
+    game_over: Res<GameOver>,
+    mut controls: ResMut<VersusControls>,
+    mut fire_orders: EventWriter<EnemyFireOrder>,
+    mut dive_orders: EventWriter<DiveOrder>,
+    slot_query: Query<&FormationSlot, With<Enemy>>,
+) {
+    controls.fire_cooldown.tick(time.delta());
+    controls.dive_cooldown.tick(time.delta());
+    if game_over.0 {
+        return;
+    }
+
+    let columns = slot_query.iter().map(|slot| slot.col).max().map_or(1, |max| max + 1);
+    if action_state.just_pressed(PlayerAction::VersusLeft) {
+        controls.column = (controls.column + columns - 1) % columns;
+    }
+    if action_state.just_pressed(PlayerAction::VersusRight) {
+        controls.column = (controls.column + 1) % columns;
+    }
+    let column = Some(controls.column);
+    if action_state.just_pressed(PlayerAction::VersusFire) && controls.fire_cooldown.finished() {
+        fire_orders.send(EnemyFireOrder { column });
+        controls.fire_cooldown.reset();
+    }
+    if action_state.just_pressed(PlayerAction::VersusDive) && controls.dive_cooldown.finished() {
+        dive_orders.send(DiveOrder { column });
+        controls.dive_cooldown.reset();
+    }
+}
+
+fn diving_enemy_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut diver_query: Query<(Entity, &mut Transform, &mut Diving)>,
+    player_query: Query<&Transform, (With<Player>, Without<Diving>)>,
+    palette: Res<Palette>,
+    config: Res<GameConfig>,
+    flip: Res<GravityFlip>,
+    movement: Res<EnemyMovement>,
+    windows: Query<&Window>,
+) {
+    let player_x = player_query.get_single().map(|tf| tf.translation.x).ok();
+    let half_height = windows.get_single().map(|window| window.height() / 2.0).unwrap_or(300.0);
+    let dt = time.delta_seconds();
+
+    for (entity, mut transform, mut diving) in diver_query.iter_mut() {
+        diving.elapsed += dt;
+
+        // Peel off upwards and to the side first, then curve down into the dive
+        let climb = (1.0 - diving.elapsed * 2.0).max(-1.0);
+        let swing = diving.swing * DIVE_SWING * (diving.elapsed * PI).cos().max(0.0);
+        let steer = player_x
+            .map(|x| (x - transform.translation.x).clamp(-DIVE_STEER_SPEED, DIVE_STEER_SPEED))
+            .unwrap_or(0.0);
+
+        transform.translation.x += (swing + steer) * dt;
+        transform.translation.y += flip.y(climb * config.dive_speed * dt);
+
+        diving.shoot_timer.tick(time.delta());
+        if diving.shoot_timer.just_finished() && climb < 0.0 {
+            // Divers spit fast bolts on their way down
+            let kind = ProjectileKind::Bolt;
+            let down = Vec2::NEG_Y * flip.y(config.enemy_bullet_speed * kind.speed_scale());
+            spawn_enemy_bullet(&mut commands, &palette, kind, transform.translation - Vec3::Y * flip.y(20.0), down);
+        }
+
+        // Galaga-style: once past the player's edge, reappear beyond the far one and fly back into the slot
+        if diving.rejoin && flip.y(transform.translation.y) < -half_height {
+            let from = Vec2::new(transform.translation.x, flip.y(half_height));
+            transform.translation = from.extend(transform.translation.z);
+            commands.entity(entity).remove::<Diving>().insert(FlyingIn { from, target: diving.home + movement.drift, t: 0.0 });
+        }
+    }
+}
+
+fn enemy_bullet_movement(
+    mut query: Query<(&mut Transform, &mut Velocity, &ProjectileKind, &mut ProjectileAge), With<EnemyBullet>>,
+    player_query: Query<&Transform, (With<Player>, Without<EnemyBullet>)>,
+    palette: Res<Palette>,
+    time: Res<Time>,
+    flip: Res<GravityFlip>,
+) {
+    let dt = time.delta_seconds();
+    let player_x = player_query.get_single().map(|tf| tf.translation.x).ok();
+    for (mut transform, mut velocity, kind, mut age) in query.iter_mut() {
+        age.0 += dt;
+        if *kind == ProjectileKind::Homing {
+            // Turn toward the player's column, but never faster than the turn rate allows
+            if let Some(player_x) = player_x {
+                let desired = Vec2::new(player_x - transform.translation.x, flip.y(-velocity.0.length()));
+                let turn = velocity.0.angle_between(desired).clamp(-HOMING_TURN_RATE * dt, HOMING_TURN_RATE * dt);
+                velocity.0 = Vec2::from_angle(turn).rotate(velocity.0);
+                transform.rotation = palette.enemy_bullet_rotation(velocity.0);
+            }
+        }
+        // Wigglers and zig-zags drift sideways relative to their heading
+        let side = velocity.0.perp().normalize_or_zero();
+        let drift = match kind {
+            ProjectileKind::Bolt | ProjectileKind::Homing => 0.0,
+            ProjectileKind::Wiggler => (age.0 * WIGGLE_FREQUENCY).cos() * WIGGLE_AMPLITUDE * WIGGLE_FREQUENCY,
+            ProjectileKind::ZigZag => {
+                if ((age.0 / ZIGZAG_PERIOD) as u32).is_multiple_of(2) { ZIGZAG_SPEED } else { -ZIGZAG_SPEED }
+            }
+        };
+        transform.translation += (velocity.0 + side * drift).extend(0.0) * dt;
+    }
+}
+
+fn enemy_bullet_player_collision(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform, &Sprite), With<EnemyBullet>>,
+    player_query: Query<(Entity, &Transform, &Sprite), With<Player>>,
+    mut player_damaged_events: EventWriter<PlayerDamagedEvent>,
+) {
+    for (player_entity, player_tf, player_sprite) in player_query.iter() {
+        let player_size = player_sprite.custom_size.unwrap_or(Vec2::ZERO);
+        let player_pos = player_tf.translation;
+        for (bullet_entity, bullet_tf, _bullet_sprite) in bullet_query.iter() {
+            let bullet_pos = bullet_tf.translation;
+            let collision = bullet_pos.x < player_pos.x + player_size.x / 2.0
+                && bullet_pos.x > player_pos.x - player_size.x / 2.0
+                && bullet_pos.y < player_pos.y + player_size.y / 2.0
+                && bullet_pos.y > player_pos.y - player_size.y / 2.0;
+            if collision {
+                commands.entity(bullet_entity).despawn();
+                player_damaged_events.send(PlayerDamagedEvent { player: player_entity, lethal: false });
+                // One hit per frame; other bullets overlapping the ship stay for the next check
+                break;
+            }
+        }
+    }
+}
+
+// Sole owner of what a hit does to the player: shield, lives, respawn and game over
+fn player_damage_handler(
+    mut commands: Commands,
+    mut player_damaged_events: EventReader<PlayerDamagedEvent>,
+    mut player_query: Query<(&Transform, Option<&mut Shield>, Has<net::CoopPlayer>), Or<(With<Player>, With<net::CoopPlayer>)>>,
+    mut pending_game_over: ResMut<PendingGameOver>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut lives: ResMut<PlayerLives>,
+    assets: Res<GameAssets>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut rumble_events: EventWriter<rumble::Rumble>,
+    new_game_plus: Res<new_game_plus::NewGamePlus>,
+    flip: Res<GravityFlip>,
+) {
+    // Only the first hit on a ship counts; the respawned one starts clean
+    let mut destroyed = Vec::new();
+    for event in player_damaged_events.read() {
+        // Both ships draw from the same pool of lives, so a hit on either can end the run
+        if lives.0 == 0 {
+            break;
+        }
+        // A hit in the same frame may already have destroyed this ship
+        if destroyed.contains(&event.player) {
+            continue;
+        }
+        let Ok((player_tf, shield, is_coop)) = player_query.get_mut(event.player) else {
+            continue;
+        };
+        // A charged shield soaks up the hit and starts recharging from scratch
+        if let Some(mut shield) = shield
+            && shield.charged
+            && !event.lethal
+        {
+            shield.charged = false;
+            shield.recharge.reset();
+            damaged_events.send(Damaged { entity: event.player });
+            rumble_events.send(rumble::Rumble { strength: 0.3, seconds: 0.2 });
+            println!("Shield absorbed the hit!");
+            continue;
+        }
+        commands.entity(event.player).despawn_recursive();
+        destroyed.push(event.player);
+        spawn_death_debris(&mut commands, player_tf.translation);
+        rumble_events.send(rumble::Rumble { strength: 1.0, seconds: 0.5 });
+        lives.0 = if event.lethal { 0 } else { lives.0.saturating_sub(1) };
+        if lives.0 > 0 && is_coop {
+            // net::spawn_coop_player brings the partner back once its ship is gone
+            println!("Co-op partner was hit! Lives left: {}", lives.0);
+        } else if lives.0 > 0 {
+            println!("You were hit! Lives left: {}", lives.0);
+            spawn_player_ship(&mut commands, &assets, new_game_plus.weapon_tier, *flip);
+        } else {
+            // The final explosion plays out in slow motion before the game over screen
+            pending_game_over.0 = Some(Timer::from_seconds(DEATH_SLOW_MOTION_SECONDS, TimerMode::Once));
+            slow_motion.start(DEATH_TIME_SCALE, DEATH_SLOW_MOTION_SECONDS);
+            println!("You were hit! Game Over!");
+        }
+    }
+}
+
+fn finish_pending_game_over(time: Res<Time<Real>>, mut pending_game_over: ResMut<PendingGameOver>, mut game_over: ResMut<GameOver>) {
+    let Some(timer) = pending_game_over.0.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        pending_game_over.0 = None;
+        game_over.0 = true;
+    }
+}
+
+fn spawn_death_debris(commands: &mut Commands, position: Vec3) {
+    for index in 0..DEATH_DEBRIS {
+        let angle = index as f32 / DEATH_DEBRIS as f32 * 2.0 * PI;
+        // Alternate fast and slow pieces so the burst doesn't read as a perfect ring
+        let speed = DEATH_DEBRIS_SPEED * if index % 2 == 0 { 1.0 } else { 0.6 };
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(1.0, 0.6, 0.2),
+                    custom_size: Some(Vec2::splat(5.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Debris,
+            Velocity(Vec2::from_angle(angle) * speed),
+            Lifetime(Timer::from_seconds(DEATH_DEBRIS_LIFETIME, TimerMode::Once)),
+            WaveScoped,
+        ));
+    }
+}
+
+// Comes back after REGEN_SHIELD_SECONDS unless the invader is finished off first
+fn regenerate_enemy_shields(mut commands: Commands, time: Res<Time>, mut query: Query<&mut RegenShield>) {
+    for mut shield in query.iter_mut() {
+        if shield.up || !shield.regen.tick(time.delta()).just_finished() {
+            continue;
+        }
+        shield.up = true;
+        commands.entity(shield.ring).insert(Visibility::Inherited);
+    }
+}
+
+fn debris_movement(time: Res<Time>, mut query: Query<(&mut Transform, &mut Sprite, &Velocity, &Lifetime), With<Debris>>) {
+    for (mut transform, mut sprite, velocity, lifetime) in query.iter_mut() {
+        transform.translation += velocity.0.extend(0.0) * time.delta_seconds();
+        sprite.color.set_a(lifetime.0.fraction_remaining());
+    }
+}
+
+fn attach_player_shield(
+    mut commands: Commands,
+    player_query: Query<Entity, Added<Player>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<GameConfig>,
+) {
+    for player in player_query.iter() {
+        commands
+            .entity(player)
+            .insert(Shield {
+                charged: true,
+                recharge: Timer::from_seconds(config.shield_recharge_time, TimerMode::Once),
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(meshes.add(Circle::new(SHIELD_RADIUS))),
+                        material: materials.add(Color::rgba(0.3, 0.7, 1.0, 0.25)),
+                        transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                        ..default()
+                    },
+                    ShieldBubble,
+                ));
+            });
+    }
+}
+
+fn recharge_shield(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Shield, &Children), With<Player>>,
+    mut bubble_query: Query<&mut Visibility, With<ShieldBubble>>,
+) {
+    for (mut shield, children) in player_query.iter_mut() {
+        if !shield.charged {
+            shield.recharge.tick(time.delta());
+            if shield.recharge.finished() {
+                shield.charged = true;
+                println!("Shield recharged");
+            }
+        }
+        for &child in children.iter() {
+            if let Ok(mut visibility) = bubble_query.get_mut(child) {
+                *visibility = if shield.charged { Visibility::Inherited } else { Visibility::Hidden };
+            }
+        }
+    }
+}
+
+fn game_over_screen(
+    game_over: Res<GameOver>,
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut game_over_text_query: Query<Entity, With<GameOverText>>,
+    wave_won: Res<WaveWon>,
+    locale: Res<Locale>,
+    palette: Res<Palette>,
+    menu: Res<GameOverMenu>,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    state: Res<State<GameState>>,
+    clock: Res<WaveClock>,
+    ruleset: Res<Ruleset>,
+    mode: Res<GameMode>,
+    survival_run: Res<survival::SurvivalRun>,
+    level: Res<Level>,
+) {
+    if game_over.is_changed() || locale.is_changed() || palette.is_changed() || menu.is_changed() || state.is_changed() {
+        for entity in game_over_text_query.iter_mut() {
+            commands.entity(entity).despawn();
+        }
+        if game_over.0 && *state.get() != GameState::MainMenu {
+            let survived = (*mode == GameMode::Survival).then_some((clock.elapsed, survival_run.kills));
+            let mut message = game_over_panel(&locale, &menu, &stats, score.0, *ruleset, survived);
+            let mut color = palette.lose_text;
+            if wave_won.0 {
+                message = format!(
+                    "{}\n{} {} ({:.1}s)",
+                    locale.get("game_over.won"),
+                    locale.get("game_over.time_bonus"),
+                    clock.time_bonus,
+                    clock.elapsed
+                );
+                // Time attack is scored on the clock alone, so the split replaces the bonus
+                if *mode == GameMode::TimeAttack {
+                    let key = if level.0 >= time_attack::WAVES { "time_attack.done" } else { "time_attack.cleared" };
+                    message = format!("{}\n{} {:.2}s", locale.get(key), locale.get("time_attack.split"), clock.elapsed);
+                }
+                if *mode == GameMode::BossRush {
+                    let key = if level.0 >= boss_rush::last_level() { "boss_rush.done" } else { "boss_rush.cleared" };
+                    message = locale.get(key).to_string();
+                }
+                if new_game_plus::offers_new_game_plus(*mode, level.0) {
+                    message = new_game_plus::offer_text(&locale);
+                }
+                color = palette.win_text;
+            }
+            commands.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        message,
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 60.0,
+                            color,
+                        },
+                    ),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(25.0),
+                        top: Val::Percent(40.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                GameOverText,
+            ));
+        } else {
+            for entity in game_over_text_query.iter_mut() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+// Survival runs are summed up by time survived and kills alone
+fn game_over_panel(
+    locale: &Locale,
+    menu: &GameOverMenu,
+    stats: &RunStats,
+    score: u32,
+    ruleset: Ruleset,
+    survived: Option<(f32, u32)>,
+) -> String {
+    let mut lines = vec![locale.get("game_over.lost").to_string()];
+    if let Some((elapsed, kills)) = survived {
+        lines.push(survival::survival_summary(locale, elapsed, kills));
+    } else {
+        lines.push(format!("{} {}", locale.get("game_over.score"), score));
+        lines.push(format!("{} {}", locale.get("game_over.waves"), stats.waves_cleared));
+        lines.push(format!("{} {:.0}%", locale.get("game_over.accuracy"), stats.accuracy()));
+        if ruleset.combos() {
+            lines.push(format!("{} {}", locale.get("game_over.best_combo"), stats.best_combo));
+        }
+    }
+    lines.push(String::new());
+    for (index, option) in GameOverOption::ALL.iter().enumerate() {
+        let cursor = if index == menu.selected { ">" } else { " " };
+        let mut label = locale.get(option.label_key()).to_string();
+        #[cfg(feature = "online-leaderboard")]
+        if *option == GameOverOption::SubmitScore && menu.submitted {
+            label = locale.get("game_over.submitted").to_string();
+        }
+        if *option == GameOverOption::Retry {
+            label.push_str(" (R)");
+        }
+        lines.push(format!("{} {}", cursor, label));
+    }
+    lines.join("\n")
+}
+
+// Arrow keys or the d-pad pick an option; Enter, Space or the south button confirms
+fn navigate_game_over_menu(
+    action_state: Res<ActionState>,
+    game_over: Res<GameOver>,
+    wave_won: Res<WaveWon>,
+    mut menu: ResMut<GameOverMenu>,
+    mut finished_events: EventReader<RunFinished>,
+    mut restart_events: EventWriter<RestartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+    #[cfg(feature = "online-leaderboard")] mut submit_events: EventWriter<SubmitScore>,
+) {
+    if let Some(run) = finished_events.read().last() {
+        menu.last_run = Some(*run);
+    }
+    if !game_over.0 || wave_won.0 {
+        if menu.selected != 0 || menu.submitted {
+            menu.selected = 0;
+            menu.submitted = false;
+        }
+        return;
+    }
+    if action_state.just_pressed(PlayerAction::Restart) {
+        restart_events.send(RestartRun);
+        return;
+    }
+    let count = GameOverOption::ALL.len();
+    if action_state.just_pressed(PlayerAction::MenuUp) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if action_state.just_pressed(PlayerAction::MenuDown) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    if !action_state.just_pressed(PlayerAction::Confirm) {
+        return;
+    }
+    match GameOverOption::ALL[menu.selected] {
+        GameOverOption::Retry => {
+            restart_events.send(RestartRun);
+        }
+        GameOverOption::MainMenu => next_state.set(GameState::MainMenu),
+        #[cfg(feature = "online-leaderboard")]
+        GameOverOption::SubmitScore => {
+            if !menu.submitted && let Some(run) = menu.last_run {
+                submit_events.send(SubmitScore(run));
+                menu.submitted = true;
+            }
+        }
+    }
+}
+
+fn show_main_menu(mut commands: Commands, assets: Res<GameAssets>, profile: Res<CurrentProfile>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(35.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        MainMenuText,
+        Localized("main_menu.title"),
+    ));
+    if SavedRun::exists(&profile) {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(25.0),
+                    top: Val::Percent(60.0),
+                    ..default()
+                },
+                z_index: ZIndex::Global(15),
+                ..default()
+            },
+            MainMenuText,
+            Localized("main_menu.continue"),
+        ));
+    }
+    commands.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::YELLOW,
+                }),
+            ]),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(70.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        MainMenuText,
+        RulesetText,
+        Localized("main_menu.ruleset"),
+    ));
+    commands.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                TextSection::new(
+                    profile.0.clone(),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::YELLOW,
+                    },
+                ),
+            ]),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(78.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        MainMenuText,
+        Localized("main_menu.profile"),
+    ));
+    commands.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::YELLOW,
+                }),
+            ]),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(86.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        MainMenuText,
+        DailyText,
+        Localized("main_menu.daily"),
+    ));
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(93.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        MainMenuText,
+        Localized("main_menu.boss_rush"),
+    ));
+}
+
+// Everything kept per profile is read again after switching
+fn reload_profile_data(
+    profile: Res<CurrentProfile>,
+    mut settings: ResMut<Settings>,
+    mut high_scores: ResMut<HighScores>,
+    mut player_name: ResMut<PlayerName>,
+) {
+    if profile.is_changed() && !profile.is_added() {
+        *settings = Settings::load(&profile);
+        *high_scores = HighScores::load(&profile);
+        *player_name = PlayerName::for_profile(&profile);
+    }
+}
+
+// Left/Right on the main menu switch between the Classic and Modern rules
+fn select_ruleset(action_state: Res<ActionState>, mut ruleset: ResMut<Ruleset>) {
+    let step = action_state.just_pressed(PlayerAction::MoveRight) as i32 - action_state.just_pressed(PlayerAction::MoveLeft) as i32;
+    if step == 0 {
+        return;
+    }
+    *ruleset = ruleset.cycle(step);
+    println!("Ruleset: {:?}", *ruleset);
+}
+
+fn update_ruleset_text(
+    ruleset: Res<Ruleset>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, Ref<RulesetText>)>,
+) {
+    for (mut text, marker) in query.iter_mut() {
+        if ruleset.is_changed() || locale.is_changed() || marker.is_added() {
+            text.sections[1].value = locale.get(ruleset.label_key()).to_string();
+        }
+    }
+}
+
+// C on the main menu restores the saved run; its enemies fly back into their old spots
+fn resume_saved_run(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<PlayerLives>,
+    mut level: ResMut<Level>,
+    mut bombs: ResMut<Bombs>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    mut stats: ResMut<RunStats>,
+    assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+    mut ruleset: ResMut<Ruleset>,
+    (profile, layout, palette, windows): (Res<CurrentProfile>, Res<LevelLayout>, Res<Palette>, Query<&Window>),
+    (mut new_game_plus, mut flip): (ResMut<new_game_plus::NewGamePlus>, ResMut<GravityFlip>),
+) {
+    if !action_state.just_pressed(PlayerAction::Continue) {
+        return;
+    }
+    let Some(run) = SavedRun::take(&profile) else {
+        return;
+    };
+    game_over.0 = false;
+    wave_won.0 = false;
+    score.0 = run.score;
+    lives.0 = run.lives;
+    level.0 = run.level;
+    bombs.0 = run.bombs;
+    enemy_speed.0 = config.enemy_speed_for_level(run.level) * run.new_game_plus.prestige.aggression();
+    *stats = RunStats::default();
+    *new_game_plus = run.new_game_plus;
+    *ruleset = run.ruleset;
+    // The run skips the level intro, so the flip is decided here
+    *flip = GravityFlip(layout.gravity_flipped(run.level));
+    spawn_player_ship(&mut commands, &assets, run.weapon_tier, *flip);
+    // Cover isn't saved either; it comes back whole
+    if let Ok(window) = windows.get_single() {
+        build_bunkers(&mut commands, &assets, &palette, window, *flip);
+    }
+    // Bosses aren't saved, so a boss wave starts the fight over
+    if is_boss_level(*ruleset, run.level) {
+        spawn_boss(&mut commands, &assets, *flip);
+    }
+    for enemy in run.enemies {
+        let side = if enemy.x < 0.0 { -1.0 } else { 1.0 };
+        let slot = FormationSlot { col: enemy.col, row: enemy.row };
+        let from = Vec2::new(side * 700.0, flip.y(400.0));
+        let entity = spawn_enemy(&mut commands, &assets, slot, enemy.health, enemy.elite, from, Vec2::new(enemy.x, enemy.y));
+        if enemy.shielded {
+            attach_regen_shield(&mut commands, &assets, entity);
+        }
+    }
+    println!("Resumed saved run at level {}", run.level);
+    next_state.set(GameState::Playing);
+}
+
+fn start_from_main_menu(
+    action_state: Res<ActionState>,
+    mut restart_events: EventWriter<RestartRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+    quit_dialog: Res<QuitDialog>,
+) {
+    if action_state.just_pressed(PlayerAction::Confirm) && !quit_dialog.open {
+        restart_events.send(RestartRun);
+        next_state.set(GameState::LevelIntro);
+    }
+}
+
+fn hide_main_menu(mut commands: Commands, text_query: Query<Entity, With<MainMenuText>>) {
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn restart_game(
+    mut restart_events: EventReader<RestartRun>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<PlayerLives>,
+    mut level: ResMut<Level>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    mut bombs: ResMut<Bombs>,
+    config: Res<GameConfig>,
+    mut stats: ResMut<RunStats>,
+    mut pending_game_over: ResMut<PendingGameOver>,
+    mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if restart_events.read().count() > 0 {
+        wave_won.0 = false;
+        pending_game_over.0 = None;
+        *stats = RunStats::default();
+        score.0 = 0;
+        lives.0 = 3;
+        level.0 = if *mode == GameMode::BossRush { boss_rush::first_level() } else { 1 };
+        game_over.0 = false;
+        enemy_speed.0 = config.enemy_speed;
+        bombs.0 = config.bombs_per_level;
+        next_state.set(GameState::LevelIntro);
+    }
+}
+
+// === UI SYSTEMS ===
+fn setup_score_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Score: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ScoreText,
+        Localized("hud.score"),
+    ));
+}
+
+fn update_score_text(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if score.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[1].value = score.0.to_string();
+        }
+    }
+}
+
+fn setup_lives_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Lives: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        LivesText,
+        Localized("hud.lives"),
+    ));
+}
+
+fn update_lives_text(lives: Res<PlayerLives>, mut query: Query<&mut Text, With<LivesText>>) {
+    if lives.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[1].value = lives.0.to_string();
+        }
+    }
+}
+
+fn setup_level_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Level: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(70.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        LevelText,
+        Localized("hud.level"),
+    ));
+}
+
+fn update_level_text(level: Res<Level>, mut query: Query<&mut Text, With<LevelText>>) {
+    if level.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[1].value = level.0.to_string();
+        }
+    }
+}
+
+fn setup_wave_progress_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Enemies: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(130.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        WaveProgressText,
+        Localized("hud.enemies"),
+    ));
+}
+
+// Counts up as a wave's enemies spawn and down on each kill; divers that fly off are caught by the clamp
+fn update_wave_progress(
+    level: Res<Level>,
+    mut progress: ResMut<WaveProgress>,
+    mut killed_events: EventReader<EnemyKilled>,
+    added_query: Query<(), Added<Enemy>>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut text_query: Query<&mut Text, With<WaveProgressText>>,
+) {
+    if level.is_changed() {
+        *progress = WaveProgress::default();
+    }
+    let added = added_query.iter().count() as u32;
+    let killed = killed_events.read().count() as u32;
+    let alive = enemy_query.iter().count() as u32;
+    let remaining = (progress.remaining + added).saturating_sub(killed).min(alive);
+    if added > 0 || remaining != progress.remaining {
+        progress.total += added;
+        progress.remaining = remaining;
+    }
+    if progress.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[1].value = format!("{}/{}", progress.remaining, progress.total);
+        }
+    }
+}
+
+fn setup_bombs_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Bombs: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(100.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        BombsText,
+        Localized("hud.bombs"),
+    ));
+}
+
+fn update_bombs_text(bombs: Res<Bombs>, mut query: Query<&mut Text, With<BombsText>>) {
+    if bombs.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[1].value = bombs.0.to_string();
+        }
+    }
+}
+
+fn setup_heat_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(15.0),
+                    right: Val::Px(10.0),
+                    width: Val::Px(150.0),
+                    height: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HeatBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::ORANGE.into(),
+                    ..default()
+                },
+                HeatBarFill,
+            ));
+        });
+}
+
+fn update_heat_bar(
+    settings: Res<Settings>,
+    heat: Res<Heat>,
+    palette: Res<Palette>,
+    mut bar_query: Query<&mut Visibility, With<HeatBar>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<HeatBarFill>>,
+) {
+    for mut visibility in bar_query.iter_mut() {
+        *visibility = if settings.overheat { Visibility::Inherited } else { Visibility::Hidden };
+    }
+    for (mut style, mut background) in fill_query.iter_mut() {
+        style.width = Val::Percent(heat.level * 100.0);
+        background.0 = if heat.lockout.is_some() { palette.heat_locked } else { palette.heat };
+    }
+}
+
+// Small bar under the ship area: refills as the shot cooldown runs down, and tracks charge while Space is held
+fn setup_cooldown_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(15.0),
+                    left: Val::Percent(45.0),
+                    width: Val::Percent(10.0),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            CooldownBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::GREEN.into(),
+                    ..default()
+                },
+                CooldownBarFill,
+            ));
+        });
+}
+
+fn update_cooldown_bar(
+    shoot_timer: Res<ShootTimer>,
+    charge: Res<ChargeShot>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+    player_query: Query<(), With<Player>>,
+    mut bar_query: Query<&mut Visibility, With<CooldownBar>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<CooldownBarFill>>,
+) {
+    for mut visibility in bar_query.iter_mut() {
+        *visibility = if player_query.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+    }
+    let (progress, color) = if charge.held > 0.0 {
+        let progress = (charge.held / config.charge_shot_time).min(1.0);
+        (progress, if progress >= 1.0 { palette.charged_bullet } else { palette.shot_charge })
+    } else if shoot_timer.0.finished() {
+        (1.0, palette.shot_ready)
+    } else {
+        (shoot_timer.0.fraction(), palette.shot_cooldown)
+    };
+    for (mut style, mut background) in fill_query.iter_mut() {
+        style.width = Val::Percent(progress * 100.0);
+        background.0 = color;
+    }
+}
+
+fn setup_settings_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(25.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        SettingsText,
+    ));
+}
+
+fn setup_quit_dialog_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(40.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(30),
+            ..default()
+        },
+        QuitDialogText,
+    ));
+}
+
+// Quitting from the menu or after game over loses nothing, so only a live run needs the prompt on close
+fn request_quit(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut close_events: EventReader<WindowCloseRequested>,
+    state: Res<State<GameState>>,
+    game_over: Res<GameOver>,
+    mut dialog: ResMut<QuitDialog>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if dialog.open {
+        close_events.clear();
+        return;
+    }
+    if close_events.read().last().is_some() {
+        if game_over.0 || *state.get() == GameState::MainMenu {
+            exit_events.send(AppExit);
+        } else {
+            dialog.open = true;
+        }
+    }
+    if *state.get() == GameState::MainMenu && keyboard_input.just_pressed(KeyCode::Escape) {
+        dialog.open = true;
+    }
+}
+
+fn answer_quit_dialog(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut dialog: ResMut<QuitDialog>,
+    mut exit_events: EventWriter<AppExit>,
+    game_over: Res<GameOver>,
+    state: Res<State<GameState>>,
+    level: Res<Level>,
+    score: Res<Score>,
+    lives: Res<PlayerLives>,
+    bombs: Res<Bombs>,
+    player_query: Query<&WeaponState, With<Player>>,
+    enemy_query: Query<(&Transform, &FormationSlot, &Health, Has<Elite>, Has<RegenShield>, Option<&FlyingIn>), With<Enemy>>,
+    profile: Res<CurrentProfile>,
+    new_game_plus: Res<new_game_plus::NewGamePlus>,
+    mode: Res<GameMode>,
+    ruleset: Res<Ruleset>,
+) {
+    if !dialog.open {
+        return;
+    }
+    let mid_run = !game_over.0 && *state.get() != GameState::MainMenu;
+    // A saved run always resumes as Arcade, so any other mode would come back with the wrong
+    // level steps, pickups and leaderboard
+    if mid_run && *mode == GameMode::Arcade && keyboard_input.just_pressed(KeyCode::KeyS) {
+        let enemies = enemy_query
+            .iter()
+            .map(|(transform, slot, health, elite, shielded, flying_in)| {
+                // Enemies still swooping in are saved at their formation slot
+                let position = flying_in.map(|flying_in| flying_in.target).unwrap_or(transform.translation.truncate());
+                SavedEnemy { col: slot.col, row: slot.row, x: position.x, y: position.y, health: health.0, elite, shielded }
+            })
+            .collect();
+        SavedRun {
+            level: level.0,
+            score: score.0,
+            lives: lives.0,
+            bombs: bombs.0,
+            weapon_tier: player_query.get_single().map(|weapon| weapon.tier).unwrap_or(1),
+            enemies,
+            new_game_plus: new_game_plus.clone(),
+            ruleset: *ruleset,
+        }
+        .save(&profile);
+        println!("Run saved, quitting");
+        exit_events.send(AppExit);
+    } else if keyboard_input.just_pressed(KeyCode::KeyY) {
+        println!("Quitting");
+        exit_events.send(AppExit);
+    } else if keyboard_input.just_pressed(KeyCode::KeyN) || keyboard_input.just_pressed(KeyCode::Escape) {
+        dialog.open = false;
+    }
+}
+
+fn update_quit_dialog_text(
+    dialog: Res<QuitDialog>,
+    locale: Res<Locale>,
+    game_over: Res<GameOver>,
+    state: Res<State<GameState>>,
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    mut query: Query<(&mut Text, &mut Visibility), With<QuitDialogText>>,
+) {
+    if !dialog.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let mid_run = !game_over.0 && *state.get() != GameState::MainMenu;
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if dialog.open { Visibility::Inherited } else { Visibility::Hidden };
+        let mut lines = vec![locale.get("quit.title").to_string()];
+        if mid_run {
+            lines.push(format!("{} {}", locale.get("quit.run_lost"), score.0));
+            if *mode == GameMode::Arcade {
+                lines.push(locale.get("quit.save").to_string());
+            }
+        }
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn settings_closed(menu: Res<SettingsMenu>) -> bool {
+    !menu.open
+}
+
+impl SlowMotion {
+    fn start(&mut self, factor: f32, seconds: f32) {
+        self.factor = factor;
+        self.remaining = seconds;
+    }
+
+    fn scale(&self) -> f32 {
+        if self.remaining > 0.0 {
+            self.factor
+        } else {
+            1.0
+        }
+    }
+}
+
+fn toggle_settings_menu(keyboard_input: Res<ButtonInput<KeyCode>>, mut menu: ResMut<SettingsMenu>) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        menu.open = !menu.open;
+    }
+}
+
+// Freeze the world while paused or while the player is fiddling with settings
+fn sync_virtual_time(
+    state: Res<State<GameState>>,
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    console: Res<DevConsole>,
+    quit_dialog: Res<QuitDialog>,
+    real_time: Res<Time<Real>>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    slow_motion.remaining = (slow_motion.remaining - real_time.delta_seconds()).max(0.0);
+    let speed = settings.game_speed.factor() * slow_motion.scale();
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+    let frozen = *state.get() == GameState::Paused || menu.open || console.open || quit_dialog.open;
+    if frozen && !time.is_paused() {
+        time.pause();
+    } else if !frozen && time.is_paused() {
+        time.unpause();
+    }
+}
+
+fn toggle_pause(
+    action_state: Res<ActionState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if action_state.just_pressed(PlayerAction::Pause) {
+        next_state.set(match state.get() {
+            GameState::Playing => GameState::Paused,
+            GameState::Paused => GameState::Playing,
+            // The countdown is only a few seconds; let it finish first
+            GameState::Loading | GameState::LevelIntro | GameState::MainMenu => return,
+        });
+    }
+}
+
+fn auto_pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in focus_events.read() {
+        if !event.focused && *state.get() == GameState::Playing {
+            next_state.set(GameState::Paused);
+        }
+    }
+}
+
+fn show_pause_overlay(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(40.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        PauseOverlay,
+        Localized("pause.title"),
+    ));
+}
+
+// Every new wave passes through the intro (pausing also leaves Playing, so OnExit(Playing) can't be used)
+fn despawn_wave_entities(mut commands: Commands, query: Query<Entity, With<WaveScoped>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+impl GravityFlip {
+    // The way the player shoots: 1.0 normally, -1.0 when flipped
+    fn up(self) -> f32 {
+        if self.0 { -1.0 } else { 1.0 }
+    }
+
+    // Mirrors a height laid out for the normal playfield
+    fn y(self, y: f32) -> f32 {
+        y * self.up()
+    }
+}
+
+// Decided before anything of the new wave spawns, so it all lands on the right side
+fn set_gravity_flip(layout: Res<LevelLayout>, level: Res<Level>, mut flip: ResMut<GravityFlip>) {
+    let flipped = GravityFlip(layout.gravity_flipped(level.0));
+    if flip.set_if_neq(flipped) && flipped.0 {
+        println!("Gravity flip: the playfield is upside down this wave");
+    }
+}
+
+fn reset_gravity_flip(mut flip: ResMut<GravityFlip>) {
+    flip.set_if_neq(GravityFlip(false));
+}
+
+// Ships drawn nose-up get turned over with the playfield
+fn mirror_flipped_sprites(flip: Res<GravityFlip>, mut query: Query<&mut Sprite, Or<(Added<Player>, Added<Enemy>)>>) {
+    for mut sprite in query.iter_mut() {
+        sprite.flip_y = flip.0;
+    }
+}
+
+fn spawn_wave(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    layout: Res<LevelLayout>,
+    level: Res<Level>,
+    ruleset: Res<Ruleset>,
+    new_game_plus: Res<new_game_plus::NewGamePlus>,
+    flip: Res<GravityFlip>,
+) {
+    spawn_player_ship(&mut commands, &assets, new_game_plus.weapon_tier, *flip);
+    if is_boss_level(*ruleset, level.0) {
+        spawn_boss(&mut commands, &assets, *flip);
+    } else {
+        spawn_enemies(&mut commands, &assets, &layout, level.0, *flip);
+    }
+}
+
+fn show_level_intro(mut commands: Commands, assets: Res<GameAssets>, mut intro: ResMut<LevelIntroTimer>) {
+    intro.0 = Timer::from_seconds(LEVEL_INTRO_SECONDS, TimerMode::Once);
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(38.0),
+                top: Val::Percent(35.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        LevelIntroText,
+    ));
+}
+
+fn tick_level_intro(
+    time: Res<Time>,
+    level: Res<Level>,
+    locale: Res<Locale>,
+    mut intro: ResMut<LevelIntroTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut text_query: Query<&mut Text, With<LevelIntroText>>,
+) {
+    intro.0.tick(time.delta());
+    let countdown = intro.0.remaining_secs().ceil().max(1.0) as u32;
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("{} {}\n{}", locale.get("intro.wave"), level.0, countdown);
+    }
+    if intro.0.finished() {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn hide_level_intro(mut commands: Commands, text_query: Query<Entity, With<LevelIntroText>>) {
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn hide_pause_overlay(mut commands: Commands, overlay_query: Query<Entity, With<PauseOverlay>>) {
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn navigate_settings_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<Settings>,
+    profile: Res<CurrentProfile>,
+) {
+    if !menu.open {
+        return;
+    }
+    let count = SettingsItem::ALL.len();
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    let item = SettingsItem::ALL[menu.selected];
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        item.change(&mut settings, -1);
+        settings.save(&profile);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) || keyboard_input.just_pressed(KeyCode::Enter) {
+        item.change(&mut settings, 1);
+        settings.save(&profile);
+    }
+}
+
+fn update_settings_text(
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<SettingsText>>,
+) {
+    if !menu.is_changed() && !settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if menu.open { Visibility::Inherited } else { Visibility::Hidden };
+        let mut lines = vec![locale.get("settings.title").to_string()];
+        for (index, item) in SettingsItem::ALL.iter().enumerate() {
+            let cursor = if index == menu.selected { ">" } else { " " };
+            lines.push(format!("{} {}: {}", cursor, locale.get(item.label_key()), item.value(&settings, &locale)));
+        }
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn setup_column_cursor(mut commands: Commands, mode: Res<GameMode>) {
+    if *mode != GameMode::Versus {
+        return;
+    }
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 0.2, 0.2, 0.6),
+                custom_size: Some(Vec2::new(40.0, 6.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        ColumnCursor,
+    ));
+}
+
+fn update_column_cursor(
+    controls: Res<VersusControls>,
+    flip: Res<GravityFlip>,
+    enemy_query: Query<(&Transform, &FormationSlot), (With<Enemy>, Without<ColumnCursor>)>,
+    mut cursor_query: Query<(&mut Transform, &mut Visibility), With<ColumnCursor>>,
+) {
+    // Hover just behind the rearmost living enemy of the selected column
+    let column_top = enemy_query
+        .iter()
+        .filter(|(_, slot)| slot.col == controls.column)
+        .map(|(transform, _)| transform.translation)
+        .reduce(|a, b| if flip.y(a.y) > flip.y(b.y) { a } else { b });
+    for (mut transform, mut visibility) in cursor_query.iter_mut() {
+        match column_top {
+            Some(top) => {
+                transform.translation.x = top.x;
+                transform.translation.y = top.y + flip.y(20.0);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn setup_leaderboard_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(10.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        LeaderboardText,
+    ));
+}
+
+fn toggle_leaderboard(keyboard_input: Res<ButtonInput<KeyCode>>, mut screen: ResMut<LeaderboardScreen>) {
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        screen.open = !screen.open;
+    }
+}
+
+fn update_leaderboard_text(
+    screen: Res<LeaderboardScreen>,
+    high_scores: Res<HighScores>,
+    daily_scores: Res<daily::DailyScores>,
+    boss_rush_scores: Res<boss_rush::BossRushScores>,
+    locale: Res<Locale>,
+    #[cfg(feature = "online-leaderboard")] online: Res<OnlineLeaderboard>,
+    mut query: Query<(&mut Text, &mut Visibility), With<LeaderboardText>>,
+) {
+    let mut lines = Vec::new();
+    #[cfg(feature = "online-leaderboard")]
+    if let Some(scores) = online.scores.as_ref() {
+        lines.push(locale.get("leaderboard.online_title").to_string());
+        for (rank, entry) in scores.iter().enumerate() {
+            lines.push(format!("{:>2}. {:<12} {}", rank + 1, entry.name, entry.score));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(locale.get("leaderboard.local_title").to_string());
+        for (rank, entry) in high_scores.0.iter().enumerate() {
+            let mut line = format!("{:>2}. {:<12} {} ({} {})", rank + 1, entry.name, entry.score, locale.get("leaderboard.level"), entry.level);
+            if entry.reduced_speed {
+                line.push_str(&format!(" {}", locale.get("leaderboard.reduced_speed")));
+            }
+            lines.push(line);
+        }
+        if high_scores.0.is_empty() {
+            lines.push(locale.get("leaderboard.empty").to_string());
+        }
+    }
+    let today = daily::today();
+    if daily_scores.for_day(today).next().is_some() {
+        lines.push(String::new());
+        lines.push(format!("{} {}", locale.get("leaderboard.daily_title"), daily::date_label(today)));
+        for (rank, entry) in daily_scores.for_day(today).enumerate() {
+            lines.push(format!("{:>2}. {:<12} {} ({} {})", rank + 1, entry.name, entry.score, locale.get("leaderboard.level"), entry.level));
+        }
+    }
+    if !boss_rush_scores.0.is_empty() {
+        lines.push(String::new());
+        lines.push(locale.get("leaderboard.boss_rush_title").to_string());
+        for (rank, entry) in boss_rush_scores.0.iter().enumerate() {
+            lines.push(format!("{:>2}. {:<12} {}/{} {} {}", rank + 1, entry.name, entry.bosses, boss_rush::FIGHTS, locale.get("leaderboard.bosses"), entry.score));
+        }
+    }
+
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if screen.open { Visibility::Inherited } else { Visibility::Hidden };
+        if screen.open {
+            text.sections[0].value = lines.join("\n");
+        }
+    }
+}
+
+fn next_level(
+    action_state: Res<ActionState>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut level: ResMut<Level>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    mut bombs: ResMut<Bombs>,
+    config: Res<GameConfig>,
+    mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // A time attack ends with its last wave, a boss rush with its last boss
+    if *mode == GameMode::TimeAttack && level.0 >= time_attack::WAVES {
+        return;
+    }
+    if *mode == GameMode::BossRush && level.0 >= boss_rush::last_level() {
+        return;
+    }
+    // The final arcade level offers New Game+ instead
+    if new_game_plus::offers_new_game_plus(*mode, level.0) {
+        return;
+    }
+    // Only allow next level once the current wave has been cleared
+    if wave_won.0 && action_state.just_pressed(PlayerAction::NextLevel) {
+        wave_won.0 = false;
+        // Boss rush skips straight to the next boss level
+        level.0 += if *mode == GameMode::BossRush { BOSS_LEVEL_INTERVAL } else { 1 };
+        enemy_speed.0 += config.enemy_speed_per_level;
+        bombs.0 = config.bombs_per_level;
+        game_over.0 = false;
+        next_state.set(GameState::LevelIntro);
+    }
+}
+
+fn reload_locale(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if settings.is_changed() && settings.language != locale.language {
+        *locale = Locale::load(settings.language);
+        println!("Language switched to {}", settings.language.name());
+    }
+}
+
+// Static labels are tagged with a key and refreshed whenever the language changes
+fn apply_localized_text(
+    locale: Res<Locale>,
+    mut query: Query<(Ref<Localized>, &mut Text)>,
+) {
+    for (localized, mut text) in query.iter_mut() {
+        if locale.is_changed() || localized.is_added() {
+            text.sections[0].value = locale.get(localized.0).to_string();
+        }
+    }
+}
+
+fn update_palette(settings: Res<Settings>, mut palette: ResMut<Palette>) {
+    if settings.is_changed() && settings.palette != palette.kind {
+        *palette = Palette::for_kind(settings.palette);
+    }
+}
+
+// Shots already in flight pick up the new palette instead of keeping the old colors
+fn recolor_with_palette(
+    palette: Res<Palette>,
+    mut bullet_query: Query<(&mut Sprite, Has<Piercing>), With<Bullet>>,
+    mut enemy_bullet_query: Query<(&mut Sprite, &mut Transform, &Velocity, &ProjectileKind), (With<EnemyBullet>, Without<Bullet>)>,
+    mut power_up_query: Query<(&mut Sprite, &PowerUp), (Without<Bullet>, Without<EnemyBullet>)>,
+    mut segment_query: Query<(&mut Sprite, &BunkerSegment), (Without<PowerUp>, Without<Bullet>, Without<EnemyBullet>)>,
+    mut asteroid_query: Query<&mut Sprite, (With<Asteroid>, Without<BunkerSegment>, Without<PowerUp>, Without<Bullet>, Without<EnemyBullet>)>,
+) {
+    if !palette.is_changed() {
+        return;
+    }
+    for mut sprite in asteroid_query.iter_mut() {
+        sprite.color = palette.asteroid;
+    }
+    for (mut sprite, segment) in segment_query.iter_mut() {
+        sprite.color = if segment.hits_left < BUNKER_SEGMENT_HITS { palette.bunker_damaged } else { palette.bunker };
+    }
+    for (mut sprite, piercing) in bullet_query.iter_mut() {
+        sprite.color = if piercing { palette.charged_bullet } else { palette.player_bullet };
+    }
+    for (mut sprite, mut transform, velocity, kind) in enemy_bullet_query.iter_mut() {
+        sprite.color = palette.enemy_bullet_color(*kind);
+        sprite.custom_size = Some(palette.enemy_bullet_size(*kind));
+        transform.rotation = palette.enemy_bullet_rotation(velocity.0);
+    }
+    for (mut sprite, kind) in power_up_query.iter_mut() {
+        sprite.color = palette.power_up_color(*kind);
+    }
+}
+
+fn setup_debug_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 18.0,
+                    color: Color::GREEN,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(30),
+            ..default()
+        },
+        DebugText,
+    ));
+}
+
+fn toggle_debug_overlay(keyboard_input: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.open = !overlay.open;
+    }
+}
+
+fn update_debug_text(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    state: Res<State<GameState>>,
+    level: Res<Level>,
+    enemy_query: Query<(), With<Enemy>>,
+    bullet_query: Query<(), With<Bullet>>,
+    enemy_bullet_query: Query<(), With<EnemyBullet>>,
+    trail_query: Query<(), With<TrailSegment>>,
+    power_up_query: Query<(), With<PowerUp>>,
+    entity_query: Query<Entity>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<DebugText>>,
+) {
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if overlay.open { Visibility::Inherited } else { Visibility::Hidden };
+        if !overlay.open {
+            continue;
+        }
+        let fps = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .unwrap_or(0.0);
+        let frame_time = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+            .and_then(|frame_time| frame_time.smoothed())
+            .unwrap_or(0.0);
+        text.sections[0].value = [
+            format!("FPS: {:.0} ({:.2} ms)", fps, frame_time),
+            format!("State: {:?}  Level: {}", state.get(), level.0),
+            format!("Enemies: {}", enemy_query.iter().count()),
+            format!("Bullets: {} player / {} enemy", bullet_query.iter().count(), enemy_bullet_query.iter().count()),
+            format!("Particles: {}", trail_query.iter().count()),
+            format!("Power-ups: {}", power_up_query.iter().count()),
+            format!("Entities: {}", entity_query.iter().count()),
+        ]
+        .join("\n");
+    }
+}
+
+fn setup_console_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(40),
+            ..default()
+        },
+        ConsoleText,
+    ));
+}
+
+// Reads raw key events so typing works even though gameplay input is swallowed while open
+fn dev_console_input(
+    mut key_events: EventReader<KeyboardInput>,
+    mut console: ResMut<DevConsole>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            console.input.clear();
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                match ConsoleCommand::parse(&line) {
+                    Ok(command) => {
+                        commands.send(command);
+                        console.log.push(format!("> {}", line));
+                    }
+                    Err(error) if error.is_empty() => {}
+                    Err(error) => console.log.push(error),
+                }
+                let overflow = console.log.len().saturating_sub(CONSOLE_LOG_LINES);
+                console.log.drain(..overflow);
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(characters) => console.input.push_str(characters),
+            _ => {}
+        }
+    }
+}
+
+fn swallow_console_input(console: Res<DevConsole>, mut keyboard_input: ResMut<ButtonInput<KeyCode>>) {
+    if console.open {
+        keyboard_input.reset_all();
+    }
+}
+
+fn update_console_text(console: Res<DevConsole>, mut query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if console.open { Visibility::Inherited } else { Visibility::Hidden };
+        let mut lines = console.log.clone();
+        lines.push(format!("> {}_", console.input));
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn apply_console_commands(
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut hit_events: EventWriter<EnemyHit>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut lives: ResMut<PlayerLives>,
+    mut level: ResMut<Level>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    mut rng: ResMut<GameRng>,
+    enemy_query: Query<Entity, With<Enemy>>,
+    config: Res<GameConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut new_wave = None;
+    for command in console_commands.read() {
+        match *command {
+            ConsoleCommand::SpawnWave(wave) => new_wave = Some(wave.max(1)),
+            ConsoleCommand::SetLives(count) => lives.0 = count,
+            // Route through the normal hit pipeline so score and the win check still apply
+            ConsoleCommand::KillAll => {
+                for enemy in enemy_query.iter() {
+                    hit_events.send(EnemyHit { enemy, damage: u32::MAX, charged: false, through_shield: true });
+                }
+            }
+            ConsoleCommand::SetSpeed(speed) => enemy_speed.0 = speed,
+            ConsoleCommand::Seed(seed) => *rng = GameRng::seeded(seed),
+        }
+    }
+
+    if let Some(wave) = new_wave {
+        level.0 = wave;
+        enemy_speed.0 = config.enemy_speed_for_level(wave);
+        game_over.0 = false;
+        wave_won.0 = false;
+        next_state.set(GameState::LevelIntro);
+        println!("Console: spawned wave {}", wave);
+    }
+}
+
+fn god_mode(cheats: Res<Cheats>) -> bool {
+    cheats.god_mode
+}
+
+// Debug builds only: Ctrl+G toggles invulnerability, Ctrl+K clears the wave, Ctrl+L followed by a level
+// number jumps there once Ctrl is let go. The chord keys are swallowed before the action map sees them,
+// so they don't also steer the versus invaders or pick perks
+#[cfg(debug_assertions)]
+fn cheat_chords(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut cheats: ResMut<Cheats>,
+    mut console_commands: EventWriter<ConsoleCommand>,
+    mut level_entry: Local<Option<u32>>,
+) {
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+        KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    ];
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        if let Some(level) = level_entry.take()
+            && level > 0
+        {
+            console_commands.send(ConsoleCommand::SpawnWave(level));
+            cheats.used = true;
+            println!("Cheat: jumped to level {}", level);
+        }
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyG) {
+        cheats.god_mode = !cheats.god_mode;
+        cheats.used = true;
+        println!("Cheat: god mode {}", if cheats.god_mode { "on" } else { "off" });
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyK) {
+        console_commands.send(ConsoleCommand::KillAll);
+        cheats.used = true;
+        println!("Cheat: wave cleared");
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyL) {
+        *level_entry = Some(0);
+    }
+    if let Some(level) = level_entry.as_mut()
+        && let Some(digit) = DIGITS.iter().position(|key| keyboard_input.just_pressed(*key))
+    {
+        *level = level.saturating_mul(10).saturating_add(digit as u32);
+    }
+    for key in [KeyCode::KeyG, KeyCode::KeyK, KeyCode::KeyL].iter().chain(DIGITS.iter()) {
+        keyboard_input.reset(*key);
+    }
+}
+
+fn setup_cheat_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 20.0,
+                    color: Color::rgb(1.0, 0.3, 0.3),
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(30),
+            ..default()
+        },
+        CheatText,
+    ));
+}
+
+fn update_cheat_text(cheats: Res<Cheats>, mut query: Query<(&mut Text, &mut Visibility), With<CheatText>>) {
+    if !cheats.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if cheats.used { Visibility::Inherited } else { Visibility::Hidden };
+        text.sections[0].value = if cheats.god_mode { "CHEATS [GOD]".to_string() } else { "CHEATS".to_string() };
+    }
+}
+
+fn load_data_files(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DataHandles {
+        config: asset_server.load(CONFIG_FILE),
+        layout: asset_server.load(LEVEL_FILE),
+    });
+}
+
+// Only edits made while the game runs are staged; the initial load matches what main() already read
+fn stage_reloaded_data(
+    mut config_events: EventReader<AssetEvent<GameConfig>>,
+    mut layout_events: EventReader<AssetEvent<LevelLayout>>,
+    configs: Res<Assets<GameConfig>>,
+    layouts: Res<Assets<LevelLayout>>,
+    handles: Option<Res<DataHandles>>,
+    mut staged: ResMut<StagedAssets>,
+) {
+    let Some(handles) = handles else {
+        return;
+    };
+    for event in config_events.read() {
+        if let AssetEvent::Modified { id } = event
+            && *id == handles.config.id()
+            && let Some(config) = configs.get(*id)
+        {
+            println!("{} changed, applying on the next wave", CONFIG_FILE);
+            staged.config = Some(config.clone());
+        }
+    }
+    for event in layout_events.read() {
+        if let AssetEvent::Modified { id } = event
+            && *id == handles.layout.id()
+            && let Some(layout) = layouts.get(*id)
+        {
+            println!("{} changed, applying on the next wave", LEVEL_FILE);
+            staged.layout = Some(layout.clone());
+        }
+    }
+}
+
+fn apply_staged_data(
+    game_over: Res<GameOver>,
+    mut staged: ResMut<StagedAssets>,
+    mut config: ResMut<GameConfig>,
+    mut layout: ResMut<LevelLayout>,
+    mut enemy_shoot_timer: ResMut<EnemyShootTimer>,
+    mut dive_timer: ResMut<DiveTimer>,
+) {
+    // The wave is over once GameOver is set (won or lost), so the next spawn picks up the new values
+    if !game_over.0 {
+        return;
+    }
+    if let Some(new_config) = staged.config.take() {
+        enemy_shoot_timer.0.set_duration(Duration::from_secs_f32(new_config.enemy_shoot_cooldown));
+        dive_timer.0.set_duration(Duration::from_secs_f32(new_config.dive_interval));
+        *config = new_config;
+        println!("Applied updated {}", CONFIG_FILE);
+    }
+    if let Some(new_layout) = staged.layout.take() {
+        *layout = new_layout;
+        println!("Applied updated {}", LEVEL_FILE);
+    }
+}
@@ -0,0 +1,74 @@
+use std::marker::PhantomData;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{Asset, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+
+// Data files go through the asset server so the file watcher can hot reload them
+type LoadError = Box<dyn std::error::Error + Send + Sync>;
+
+pub struct TomlLoader<A>(PhantomData<fn() -> A>);
+
+impl<A> Default for TomlLoader<A> {
+    fn default() -> Self {
+        TomlLoader(PhantomData)
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for TomlLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = LoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<A, LoadError>> {
+        Box::pin(async move {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents).await?;
+            Ok(toml::from_str(&contents)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+}
+
+pub struct RonLoader<A> {
+    extensions: &'static [&'static str],
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> RonLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        RonLoader { extensions, marker: PhantomData }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = LoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<A, LoadError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
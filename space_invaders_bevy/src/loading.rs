@@ -0,0 +1,112 @@
+use bevy::asset::{LoadState, UntypedAssetId};
+use bevy::prelude::*;
+
+use super::{GameAssets, GameState};
+
+// The game opens on a loading screen that waits for everything in GameAssets, so the
+// first frames of the menu or the first wave never show missing text or silent effects
+#[derive(Resource)]
+pub struct LoadingTarget(pub GameState);
+
+#[derive(Component)]
+struct LoadingScreen;
+#[derive(Component)]
+struct LoadingBarFill;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Loading), show_loading_screen)
+            .add_systems(Update, track_loading.run_if(in_state(GameState::Loading)))
+            .add_systems(OnExit(GameState::Loading), hide_loading_screen);
+    }
+}
+
+impl GameAssets {
+    fn tracked_ids(&self) -> Vec<UntypedAssetId> {
+        vec![
+            self.atlas.image.id().untyped(),
+            self.font.id().untyped(),
+            self.sounds.shoot.id().untyped(),
+            self.sounds.explosion.id().untyped(),
+            self.sounds.clink.id().untyped(),
+        ]
+    }
+}
+
+fn show_loading_screen(mut commands: Commands) {
+    // Opaque backdrop so the HUD spawned at startup stays hidden until loading is done
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(40),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(16.0),
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        LoadingBarFill,
+                    ));
+                });
+        });
+}
+
+fn track_loading(
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    target: Res<LoadingTarget>,
+    mut fill_query: Query<&mut Style, With<LoadingBarFill>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let ids = assets.tracked_ids();
+    // Assets built in memory have no load state and count as ready; failed loads count too,
+    // the game carries on without them rather than sitting on this screen forever
+    let ready = ids
+        .iter()
+        .filter(|id| !matches!(asset_server.get_load_state(**id), Some(LoadState::NotLoaded | LoadState::Loading)))
+        .count();
+    for mut style in fill_query.iter_mut() {
+        style.width = Val::Percent(ready as f32 / ids.len() as f32 * 100.0);
+    }
+    if ready == ids.len() {
+        println!("Loaded {} assets", ids.len());
+        next_state.set(target.0);
+    }
+}
+
+fn hide_loading_screen(mut commands: Commands, screen_query: Query<Entity, With<LoadingScreen>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::{AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder, PathStream, Reader};
+use bevy::utils::BoxedFuture;
+
+use super::ASSET_DIR;
+
+// Files dropped into mods/assets shadow the bundled ones with the same relative path,
+// so sprites, fonts, sounds and data files can be re-skinned without a rebuild.
+pub const MODS_ASSET_DIR: &str = "mods/assets";
+
+struct OverlayAssetReader {
+    mods: FileAssetReader,
+    bundled: FileAssetReader,
+}
+
+impl AssetReader for OverlayAssetReader {
+    fn read<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            match self.mods.read(path).await {
+                Err(AssetReaderError::NotFound(_)) => match self.bundled.read(path).await {
+                    #[cfg(feature = "embed-assets")]
+                    Err(AssetReaderError::NotFound(_)) => super::embedded::EmbeddedAssetReader.read(path).await,
+                    result => result,
+                },
+                result => result,
+            }
+        })
+    }
+
+    fn read_meta<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            match self.mods.read_meta(path).await {
+                Err(AssetReaderError::NotFound(_)) => self.bundled.read_meta(path).await,
+                result => result,
+            }
+        })
+    }
+
+    fn read_directory<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        self.bundled.read_directory(path)
+    }
+
+    fn is_directory<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        self.bundled.is_directory(path)
+    }
+}
+
+// Replaces the default asset source; must be registered before DefaultPlugins
+pub fn overlay_source() -> AssetSourceBuilder {
+    let source = AssetSource::build().with_reader(|| {
+        Box::new(OverlayAssetReader {
+            mods: FileAssetReader::new(MODS_ASSET_DIR),
+            bundled: FileAssetReader::new(ASSET_DIR),
+        })
+    });
+    // A single-file build may run with no assets folder at all, and there is nothing to watch then
+    if cfg!(feature = "embed-assets") && !Path::new(ASSET_DIR).is_dir() {
+        return source;
+    }
+    source.with_watcher(AssetSource::get_default_watcher(ASSET_DIR.to_string(), Duration::from_millis(300)))
+}
+
+// Same lookup for the data files read straight from disk at startup
+pub fn data_path(relative: &str) -> PathBuf {
+    let modded = Path::new(MODS_ASSET_DIR).join(relative);
+    if modded.is_file() { modded } else { Path::new(ASSET_DIR).join(relative) }
+}
+
+// Reads a data file through the same layers as the asset server, bundled copy included
+pub fn read_data(relative: &str) -> std::io::Result<Vec<u8>> {
+    let result = std::fs::read(data_path(relative));
+    #[cfg(feature = "embed-assets")]
+    if result.is_err()
+        && let Some(bytes) = super::embedded::get(relative)
+    {
+        return Ok(bytes.to_vec());
+    }
+    result
+}
+
+pub fn read_data_to_string(relative: &str) -> std::io::Result<String> {
+    String::from_utf8(read_data(relative)?).map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
+
+pub fn data_exists(relative: &str) -> bool {
+    data_path(relative).is_file() || cfg!(feature = "embed-assets") && embedded_exists(relative)
+}
+
+#[cfg(feature = "embed-assets")]
+fn embedded_exists(relative: &str) -> bool {
+    super::embedded::get(relative).is_some()
+}
+
+#[cfg(not(feature = "embed-assets"))]
+fn embedded_exists(_relative: &str) -> bool {
+    false
+}
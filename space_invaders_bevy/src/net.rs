@@ -0,0 +1,469 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+
+use super::atlas::SpriteKind;
+use super::input::{ActionState, PlayerAction};
+use super::{
+    game_over, god_mode, player_damage_handler, Bullet, DespawnOutside, Enemy, EnemyBullet, GameAssets, GameConfig, GameOver, GameplaySet,
+    GravityFlip, Level, Palette, Player, PlayerDamagedEvent, PlayerLives, PowerUp, Score, Velocity, WaveWon, OFFSCREEN_MARGIN, PLAYER_Y,
+};
+
+// Two-player LAN co-op: the host runs the authoritative simulation and streams
+// sprite snapshots, the client only sends its inputs and mirrors what it receives.
+const INPUT_PACKET: u8 = 0;
+const SNAPSHOT_PACKET: u8 = 1;
+const MAX_PACKET_SIZE: usize = 16 * 1024;
+const COOP_PLAYER_SPEED: f32 = 300.0;
+const COOP_SHOOT_COOLDOWN: f32 = 0.3;
+
+const TEXTURE_NONE: u8 = 0;
+const TEXTURE_PLAYER: u8 = 1;
+const TEXTURE_ENEMY: u8 = 2;
+
+#[derive(Resource)]
+pub enum NetRole {
+    Host { socket: UdpSocket, peer: Option<SocketAddr> },
+    Client { socket: UdpSocket, host: SocketAddr },
+}
+
+#[derive(Component)]
+pub struct CoopPlayer {
+    shoot_timer: Timer,
+}
+
+#[derive(Component)]
+struct Mirror;
+
+#[derive(Resource, Default, Clone, Copy)]
+struct RemoteInput {
+    left: bool,
+    right: bool,
+    fire: bool,
+}
+
+struct SnapshotSprite {
+    texture: u8,
+    position: Vec2,
+    size: Vec2,
+    rotation: f32,
+    color: [u8; 4],
+}
+
+struct Snapshot {
+    score: u32,
+    lives: u32,
+    level: u32,
+    game_over: bool,
+    wave_won: bool,
+    sprites: Vec<SnapshotSprite>,
+}
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteInput>().add_systems(
+            Update,
+            (
+                (
+                    host_receive_input.in_set(GameplaySet::Input),
+                    (spawn_coop_player, (coop_player_movement, coop_player_fire).run_if(not(game_over)))
+                        .chain()
+                        .in_set(GameplaySet::Movement),
+                    enemy_bullet_coop_collision
+                        .before(player_damage_handler)
+                        .in_set(GameplaySet::Collision)
+                        .run_if(not(game_over))
+                        .run_if(not(god_mode)),
+                    // Sent last so the client sees the finished frame
+                    host_send_snapshot.in_set(GameplaySet::Ui),
+                )
+                    .run_if(is_host),
+                (
+                    client_send_input.in_set(GameplaySet::Input),
+                    client_apply_snapshot.in_set(GameplaySet::Ui),
+                )
+                    .run_if(is_client),
+            ),
+        );
+    }
+}
+
+pub fn role_from_options(host_port: Option<u16>, join: Option<SocketAddr>) -> Option<NetRole> {
+    if let Some(port) = host_port {
+        let socket = bind(&format!("0.0.0.0:{port}"))?;
+        println!("Hosting co-op game on port {port}");
+        return Some(NetRole::Host { socket, peer: None });
+    }
+    if let Some(host) = join {
+        let socket = bind("0.0.0.0:0")?;
+        println!("Joining co-op game at {host}");
+        return Some(NetRole::Client { socket, host });
+    }
+    None
+}
+
+fn bind(address: &str) -> Option<UdpSocket> {
+    let socket = UdpSocket::bind(address)
+        .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+    match socket {
+        Ok(socket) => Some(socket),
+        Err(error) => {
+            eprintln!("Could not open UDP socket on {address}: {error}");
+            None
+        }
+    }
+}
+
+pub fn is_host(role: Option<Res<NetRole>>) -> bool {
+    matches!(role.as_deref(), Some(NetRole::Host { .. }))
+}
+
+pub fn is_client(role: Option<Res<NetRole>>) -> bool {
+    matches!(role.as_deref(), Some(NetRole::Client { .. }))
+}
+
+// === HOST ===
+fn host_receive_input(mut role: ResMut<NetRole>, mut remote_input: ResMut<RemoteInput>) {
+    let NetRole::Host { socket, peer } = role.as_mut() else {
+        return;
+    };
+    let mut buffer = [0u8; 16];
+    while let Ok((length, from)) = socket.recv_from(&mut buffer) {
+        if length < 4 || buffer[0] != INPUT_PACKET {
+            continue;
+        }
+        if peer.is_none() {
+            println!("Co-op partner joined from {from}");
+        }
+        *peer = Some(from);
+        *remote_input = RemoteInput {
+            left: buffer[1] != 0,
+            right: buffer[2] != 0,
+            fire: buffer[3] != 0,
+        };
+    }
+}
+
+fn spawn_coop_player(
+    mut commands: Commands,
+    role: Res<NetRole>,
+    assets: Res<GameAssets>,
+    coop_query: Query<(), With<CoopPlayer>>,
+) {
+    let NetRole::Host { peer: Some(_), .. } = role.as_ref() else {
+        return;
+    };
+    if !coop_query.is_empty() {
+        return;
+    }
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: assets.atlas.image.clone(),
+            atlas: assets.atlas.sprite(SpriteKind::Player),
+            transform: Transform::from_xyz(60.0, PLAYER_Y, 0.0),
+            sprite: Sprite {
+                color: Color::rgb(0.6, 1.0, 0.6),
+                custom_size: Some(Vec2::new(50.0, 20.0)),
+                ..default()
+            },
+            ..default()
+        },
+        CoopPlayer {
+            shoot_timer: Timer::from_seconds(COOP_SHOOT_COOLDOWN, TimerMode::Once),
+        },
+    ));
+}
+
+fn coop_player_movement(
+    remote_input: Res<RemoteInput>,
+    time: Res<Time>,
+    windows: Query<&Window>,
+    flip: Res<GravityFlip>,
+    mut query: Query<(&mut Transform, &mut Sprite), With<CoopPlayer>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let half_width = window.width() / 2.0;
+    for (mut transform, mut sprite) in query.iter_mut() {
+        // The co-op ship outlives waves, so it follows the host's ship across a gravity flip
+        transform.translation.y = flip.y(PLAYER_Y);
+        sprite.flip_y = flip.0;
+        let mut direction = 0.0;
+        if remote_input.left {
+            direction -= 1.0;
+        }
+        if remote_input.right {
+            direction += 1.0;
+        }
+        transform.translation.x = (transform.translation.x
+            + direction * COOP_PLAYER_SPEED * time.delta_seconds())
+            .clamp(-half_width + 25.0, half_width - 25.0);
+    }
+}
+
+fn coop_player_fire(
+    mut commands: Commands,
+    remote_input: Res<RemoteInput>,
+    time: Res<Time>,
+    game_over: Res<GameOver>,
+    palette: Res<Palette>,
+    config: Res<GameConfig>,
+    flip: Res<GravityFlip>,
+    mut query: Query<(&Transform, &mut CoopPlayer)>,
+) {
+    for (transform, mut coop) in query.iter_mut() {
+        coop.shoot_timer.tick(time.delta());
+        if game_over.0 || !remote_input.fire || !coop.shoot_timer.finished() {
+            continue;
+        }
+        coop.shoot_timer.reset();
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: palette.player_bullet,
+                    custom_size: Some(Vec2::new(5.0, 15.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(transform.translation + Vec3::Y * flip.y(20.0)),
+                ..default()
+            },
+            Bullet,
+            Velocity(Vec2::Y * flip.y(config.bullet_speed)),
+            DespawnOutside(OFFSCREEN_MARGIN),
+        ));
+    }
+}
+
+// Lives, respawn and game over are left to player_damage_handler, as for the host's ship
+fn enemy_bullet_coop_collision(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform), With<EnemyBullet>>,
+    coop_query: Query<(Entity, &Transform, &Sprite), With<CoopPlayer>>,
+    mut player_damaged_events: EventWriter<PlayerDamagedEvent>,
+) {
+    for (coop_entity, coop_tf, coop_sprite) in coop_query.iter() {
+        let half_size = coop_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        let coop_pos = coop_tf.translation;
+        for (bullet_entity, bullet_tf) in bullet_query.iter() {
+            let bullet_pos = bullet_tf.translation;
+            if (bullet_pos.x - coop_pos.x).abs() < half_size.x && (bullet_pos.y - coop_pos.y).abs() < half_size.y {
+                commands.entity(bullet_entity).despawn();
+                player_damaged_events.send(PlayerDamagedEvent { player: coop_entity, lethal: false });
+                return;
+            }
+        }
+    }
+}
+
+fn host_send_snapshot(
+    role: Res<NetRole>,
+    score: Res<Score>,
+    lives: Res<PlayerLives>,
+    level: Res<Level>,
+    game_over: Res<GameOver>,
+    wave_won: Res<WaveWon>,
+    sprite_query: Query<
+        (&Transform, &Sprite, Has<Player>, Has<CoopPlayer>, Has<Enemy>),
+        Or<(With<Player>, With<CoopPlayer>, With<Enemy>, With<Bullet>, With<EnemyBullet>, With<PowerUp>)>,
+    >,
+) {
+    let NetRole::Host { socket, peer: Some(peer) } = role.as_ref() else {
+        return;
+    };
+    let sprites = sprite_query
+        .iter()
+        .map(|(transform, sprite, is_player, is_coop, is_enemy)| SnapshotSprite {
+            texture: if is_player || is_coop {
+                TEXTURE_PLAYER
+            } else if is_enemy {
+                TEXTURE_ENEMY
+            } else {
+                TEXTURE_NONE
+            },
+            position: transform.translation.truncate(),
+            size: sprite.custom_size.unwrap_or(Vec2::ONE),
+            rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+            color: sprite.color.as_rgba_u8(),
+        })
+        .collect();
+    let snapshot = Snapshot {
+        score: score.0,
+        lives: lives.0,
+        level: level.0,
+        game_over: game_over.0,
+        wave_won: wave_won.0,
+        sprites,
+    };
+    if let Err(error) = socket.send_to(&snapshot.encode(), peer) {
+        warn!("Failed to send snapshot: {error}");
+    }
+}
+
+// === CLIENT ===
+fn client_send_input(role: Res<NetRole>, action_state: Res<ActionState>) {
+    let NetRole::Client { socket, host } = role.as_ref() else {
+        return;
+    };
+    let packet = [
+        INPUT_PACKET,
+        action_state.pressed(PlayerAction::MoveLeft) as u8,
+        action_state.pressed(PlayerAction::MoveRight) as u8,
+        action_state.pressed(PlayerAction::Fire) as u8,
+    ];
+    // Sent every frame so a dropped datagram is corrected on the next one
+    let _ = socket.send_to(&packet, host);
+}
+
+fn client_apply_snapshot(
+    mut commands: Commands,
+    role: Res<NetRole>,
+    assets: Res<GameAssets>,
+    mirror_query: Query<Entity, With<Mirror>>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<PlayerLives>,
+    mut level: ResMut<Level>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+) {
+    let NetRole::Client { socket, .. } = role.as_ref() else {
+        return;
+    };
+    // Only the freshest snapshot matters, older ones are simply skipped
+    let mut buffer = vec![0u8; MAX_PACKET_SIZE];
+    let mut latest = None;
+    while let Ok(length) = socket.recv(&mut buffer) {
+        if let Some(snapshot) = Snapshot::decode(&buffer[..length]) {
+            latest = Some(snapshot);
+        }
+    }
+    let Some(snapshot) = latest else {
+        return;
+    };
+
+    for entity in mirror_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for sprite in snapshot.sprites {
+        let kind = match sprite.texture {
+            TEXTURE_PLAYER => SpriteKind::Player,
+            TEXTURE_ENEMY => SpriteKind::Enemy,
+            _ => SpriteKind::Tint,
+        };
+        let [r, g, b, a] = sprite.color;
+        commands.spawn((
+            SpriteSheetBundle {
+                texture: assets.atlas.image.clone(),
+                atlas: assets.atlas.sprite(kind),
+                sprite: Sprite {
+                    color: Color::rgba_u8(r, g, b, a),
+                    custom_size: Some(sprite.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(sprite.position.extend(0.0))
+                    .with_rotation(Quat::from_rotation_z(sprite.rotation)),
+                ..default()
+            },
+            Mirror,
+        ));
+    }
+
+    // Only touch resources that actually changed so HUD change detection stays quiet
+    if score.0 != snapshot.score {
+        score.0 = snapshot.score;
+    }
+    if lives.0 != snapshot.lives {
+        lives.0 = snapshot.lives;
+    }
+    if level.0 != snapshot.level {
+        level.0 = snapshot.level;
+    }
+    if wave_won.0 != snapshot.wave_won {
+        wave_won.0 = snapshot.wave_won;
+    }
+    if game_over.0 != snapshot.game_over {
+        game_over.0 = snapshot.game_over;
+    }
+}
+
+// === WIRE FORMAT ===
+impl Snapshot {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(20 + self.sprites.len() * 25);
+        bytes.push(SNAPSHOT_PACKET);
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        bytes.extend_from_slice(&self.lives.to_le_bytes());
+        bytes.extend_from_slice(&self.level.to_le_bytes());
+        bytes.push(self.game_over as u8 | (self.wave_won as u8) << 1);
+        bytes.extend_from_slice(&(self.sprites.len() as u16).to_le_bytes());
+        for sprite in &self.sprites {
+            bytes.push(sprite.texture);
+            for value in [sprite.position.x, sprite.position.y, sprite.size.x, sprite.size.y, sprite.rotation] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&sprite.color);
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.u8()? != SNAPSHOT_PACKET {
+            return None;
+        }
+        let score = reader.u32()?;
+        let lives = reader.u32()?;
+        let level = reader.u32()?;
+        let flags = reader.u8()?;
+        let count = reader.u16()?;
+        let mut sprites = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            sprites.push(SnapshotSprite {
+                texture: reader.u8()?,
+                position: Vec2::new(reader.f32()?, reader.f32()?),
+                size: Vec2::new(reader.f32()?, reader.f32()?),
+                rotation: reader.f32()?,
+                color: [reader.u8()?, reader.u8()?, reader.u8()?, reader.u8()?],
+            });
+        }
+        Some(Snapshot {
+            score,
+            lives,
+            level,
+            game_over: flags & 1 != 0,
+            wave_won: flags & 2 != 0,
+            sprites,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let chunk = self.bytes.get(self.offset..self.offset + N)?;
+        self.offset += N;
+        chunk.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.take().map(f32::from_le_bytes)
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::input::{ActionState, PlayerAction};
+use super::{
+    Bombs, EnemyShootTimer, EnemySpeed, GameAssets, GameConfig, GameMode, GameOver, GameState, GameplaySet, Level, Locale, Player,
+    PlayerLives, QuitDialog, RestartRun, Shield, WaveWon, WeaponState,
+};
+
+// New Game+: clearing the final arcade level offers another loop from level 1 instead of a next level.
+// The weapon tier the ship finished with and one perk picked on the spot carry over. Every loop adds
+// a prestige level, which makes the invaders faster and quicker on the trigger and multiplies the score.
+pub const FINAL_LEVEL: u32 = 20;
+const AGGRESSION_PER_PRESTIGE: f32 = 0.25;
+const SCORE_BONUS_PER_PRESTIGE: f32 = 0.5;
+const SPARE_LIVES: u32 = 2;
+const QUICK_SHIELD_FACTOR: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct Prestige(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Perk {
+    // Two extra lives when the loop starts
+    SpareLives,
+    // One extra bomb every level
+    BombBag,
+    // The shield recharges twice as fast
+    QuickShield,
+}
+
+// What New Game+ adds to the run on top of level, score and lives; saved with the run on "save and quit"
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct NewGamePlus {
+    pub prestige: Prestige,
+    // Tier every ship of the run spawns with
+    pub weapon_tier: u8,
+    pub perk: Option<Perk>,
+}
+
+#[derive(Component)]
+struct PrestigeText;
+
+pub struct NewGamePlusPlugin;
+
+impl Plugin for NewGamePlusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NewGamePlus>()
+            .add_systems(Startup, setup_prestige_ui)
+            .add_systems(
+                Update,
+                ((reset_new_game_plus, choose_perk).chain(), apply_aggression, quicken_shield).in_set(GameplaySet::Input),
+            )
+            .add_systems(OnEnter(GameState::LevelIntro), stock_bomb_bag)
+            .add_systems(Update, update_prestige_text.in_set(GameplaySet::Ui));
+    }
+}
+
+impl Default for NewGamePlus {
+    fn default() -> Self {
+        NewGamePlus {
+            prestige: Prestige::default(),
+            weapon_tier: 1,
+            perk: None,
+        }
+    }
+}
+
+impl Prestige {
+    // How hard the invaders push, 1.0 on the first loop
+    pub fn aggression(self) -> f32 {
+        1.0 + self.0 as f32 * AGGRESSION_PER_PRESTIGE
+    }
+
+    pub fn score_multiplier(self) -> f32 {
+        1.0 + self.0 as f32 * SCORE_BONUS_PER_PRESTIGE
+    }
+
+    pub fn score(self, base: u32) -> u32 {
+        (base as f32 * self.score_multiplier()).round() as u32
+    }
+}
+
+impl Perk {
+    pub const ALL: [Perk; 3] = [Perk::SpareLives, Perk::BombBag, Perk::QuickShield];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Perk::SpareLives => "new_game_plus.spare_lives",
+            Perk::BombBag => "new_game_plus.bomb_bag",
+            Perk::QuickShield => "new_game_plus.quick_shield",
+        }
+    }
+
+    fn action(self) -> PlayerAction {
+        match self {
+            Perk::SpareLives => PlayerAction::PerkOne,
+            Perk::BombBag => PlayerAction::PerkTwo,
+            Perk::QuickShield => PlayerAction::PerkThree,
+        }
+    }
+}
+
+// The final level is only final in arcade runs
+pub fn offers_new_game_plus(mode: GameMode, level: u32) -> bool {
+    mode == GameMode::Arcade && level >= FINAL_LEVEL
+}
+
+fn reset_new_game_plus(mut restart_events: EventReader<RestartRun>, mut new_game_plus: ResMut<NewGamePlus>) {
+    if restart_events.read().count() > 0 {
+        *new_game_plus = NewGamePlus::default();
+    }
+}
+
+// 1, 2 or 3 (West, North or East on a pad) on the final level's win screen picks the perk and starts the next loop
+fn choose_perk(
+    action_state: Res<ActionState>,
+    quit_dialog: Res<QuitDialog>,
+    mode: Res<GameMode>,
+    mut game_over: ResMut<GameOver>,
+    mut wave_won: ResMut<WaveWon>,
+    mut level: ResMut<Level>,
+    mut lives: ResMut<PlayerLives>,
+    mut bombs: ResMut<Bombs>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    config: Res<GameConfig>,
+    player_query: Query<&WeaponState, With<Player>>,
+    mut new_game_plus: ResMut<NewGamePlus>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !wave_won.0 || quit_dialog.open || !offers_new_game_plus(*mode, level.0) {
+        return;
+    }
+    let Some(perk) = Perk::ALL.into_iter().find(|perk| action_state.just_pressed(perk.action())) else {
+        return;
+    };
+    new_game_plus.prestige.0 += 1;
+    new_game_plus.weapon_tier = player_query.get_single().map(|weapon| weapon.tier).unwrap_or(new_game_plus.weapon_tier);
+    new_game_plus.perk = Some(perk);
+    if perk == Perk::SpareLives {
+        lives.0 += SPARE_LIVES;
+    }
+    level.0 = 1;
+    wave_won.0 = false;
+    game_over.0 = false;
+    enemy_speed.0 = config.enemy_speed * new_game_plus.prestige.aggression();
+    bombs.0 = config.bombs_per_level;
+    println!(
+        "New Game+ {} with {:?} and weapon tier {}",
+        new_game_plus.prestige.0, perk, new_game_plus.weapon_tier
+    );
+    next_state.set(GameState::LevelIntro);
+}
+
+// Runs on restarts too, which puts the fire rate back to normal
+fn apply_aggression(new_game_plus: Res<NewGamePlus>, config: Res<GameConfig>, mut shoot_timer: ResMut<EnemyShootTimer>) {
+    if !new_game_plus.is_changed() {
+        return;
+    }
+    let cooldown = config.enemy_shoot_cooldown / new_game_plus.prestige.aggression();
+    shoot_timer.0.set_duration(Duration::from_secs_f32(cooldown));
+}
+
+// After next_level and restarts have refilled the bombs
+fn stock_bomb_bag(new_game_plus: Res<NewGamePlus>, mut bombs: ResMut<Bombs>) {
+    if new_game_plus.perk == Some(Perk::BombBag) {
+        bombs.0 += 1;
+    }
+}
+
+fn quicken_shield(new_game_plus: Res<NewGamePlus>, mut shield_query: Query<&mut Shield, Added<Shield>>) {
+    if new_game_plus.perk != Some(Perk::QuickShield) {
+        return;
+    }
+    for mut shield in shield_query.iter_mut() {
+        let recharge = shield.recharge.duration().mul_f32(QUICK_SHIELD_FACTOR);
+        shield.recharge.set_duration(recharge);
+    }
+}
+
+fn setup_prestige_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 24.0,
+                color: Color::rgb(1.0, 0.85, 0.3),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        PrestigeText,
+    ));
+}
+
+fn update_prestige_text(
+    new_game_plus: Res<NewGamePlus>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<PrestigeText>>,
+) {
+    if !new_game_plus.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let prestige = new_game_plus.prestige;
+    let label = if prestige.0 == 0 {
+        String::new()
+    } else {
+        format!("{} {}  x{:.1}", locale.get("new_game_plus.prestige"), prestige.0, prestige.score_multiplier())
+    };
+    for mut text in query.iter_mut() {
+        text.sections[0].value = label.clone();
+    }
+}
+
+// Lines for the final level's win screen
+pub fn offer_text(locale: &Locale) -> String {
+    let mut lines = vec![locale.get("new_game_plus.final_cleared").to_string()];
+    for (index, perk) in Perk::ALL.iter().enumerate() {
+        lines.push(format!("{}: {}", index + 1, locale.get(perk.label_key())));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,160 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::profiles::CurrentProfile;
+use super::{storage, EnemyKilled, GameAssets, GameOver, GameState, GameplaySet, Level, Locale, RunFinished, ShotFired};
+
+// Cumulative numbers across every session, kept in the profile's player_stats.ron
+const PLAYER_STATS_FILE: &str = "player_stats.ron";
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    enemies_destroyed: u64,
+    shots_fired: u64,
+    games_played: u32,
+    best_level: u32,
+    seconds_played: f64,
+}
+
+impl PlayerStats {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(PLAYER_STATS_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(PLAYER_STATS_FILE), self, "player stats");
+    }
+}
+
+#[derive(Resource, Default)]
+struct StatsScreen {
+    open: bool,
+}
+
+#[derive(Component)]
+struct StatsText;
+
+pub struct PlayerStatsPlugin;
+
+impl Plugin for PlayerStatsPlugin {
+    fn build(&self, app: &mut App) {
+        let stats = PlayerStats::load(app.world.resource::<CurrentProfile>());
+        app.insert_resource(stats)
+            .init_resource::<StatsScreen>()
+            .add_systems(Startup, setup_stats_ui)
+            .add_systems(Update, toggle_stats_screen.in_set(GameplaySet::Input))
+            .add_systems(Update, (switch_stats_profile, track_stats, save_finished_runs, update_stats_text).chain().in_set(GameplaySet::Ui))
+            .add_systems(Last, save_on_exit);
+    }
+}
+
+fn track_stats(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    game_over: Res<GameOver>,
+    level: Res<Level>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut fired_events: EventReader<ShotFired>,
+    mut stats: ResMut<PlayerStats>,
+) {
+    let kills = killed_events.read().count() as u64;
+    let shots = fired_events.read().count() as u64;
+    if kills > 0 {
+        stats.enemies_destroyed += kills;
+    }
+    if shots > 0 {
+        stats.shots_fired += shots;
+    }
+    if level.0 > stats.best_level {
+        stats.best_level = level.0;
+    }
+    let in_run = matches!(state.get(), GameState::Playing | GameState::LevelIntro) && !game_over.0;
+    if in_run && time.delta_seconds() > 0.0 {
+        stats.seconds_played += time.delta_seconds_f64();
+    }
+}
+
+// Time played since the last save still belongs to the profile being left
+fn switch_stats_profile(profile: Res<CurrentProfile>, mut previous: Local<Option<CurrentProfile>>, mut stats: ResMut<PlayerStats>) {
+    if previous.as_ref() == Some(&*profile) {
+        return;
+    }
+    if let Some(previous) = previous.as_ref() {
+        stats.save(previous);
+        *stats = PlayerStats::load(&profile);
+    }
+    *previous = Some(profile.clone());
+}
+
+fn save_finished_runs(mut finished_events: EventReader<RunFinished>, profile: Res<CurrentProfile>, mut stats: ResMut<PlayerStats>) {
+    let games = finished_events.read().count() as u32;
+    if games > 0 {
+        stats.games_played += games;
+        stats.save(&profile);
+    }
+}
+
+fn save_on_exit(mut exit_events: EventReader<AppExit>, profile: Res<CurrentProfile>, stats: Res<PlayerStats>) {
+    if exit_events.read().count() > 0 {
+        stats.save(&profile);
+    }
+}
+
+fn setup_stats_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(25.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        StatsText,
+    ));
+}
+
+fn toggle_stats_screen(keyboard_input: Res<ButtonInput<KeyCode>>, mut screen: ResMut<StatsScreen>) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        screen.open = !screen.open;
+    }
+}
+
+fn update_stats_text(
+    screen: Res<StatsScreen>,
+    stats: Res<PlayerStats>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<StatsText>>,
+) {
+    // Time played ticks every frame, so only refresh while the screen is actually up
+    let refresh = screen.is_changed() || locale.is_changed() || (screen.open && stats.is_changed());
+    if !refresh {
+        return;
+    }
+    let minutes = (stats.seconds_played / 60.0) as u64;
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if screen.open { Visibility::Inherited } else { Visibility::Hidden };
+        text.sections[0].value = [
+            locale.get("stats.title").to_string(),
+            format!("{} {}", locale.get("stats.kills"), stats.enemies_destroyed),
+            format!("{} {}", locale.get("stats.shots"), stats.shots_fired),
+            format!("{} {}", locale.get("stats.games"), stats.games_played),
+            format!("{} {}", locale.get("stats.best_level"), stats.best_level),
+            format!("{} {}h {:02}m", locale.get("stats.time_played"), minutes / 60, minutes % 60),
+        ]
+        .join("\n");
+    }
+}
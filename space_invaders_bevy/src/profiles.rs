@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+use super::{storage, swallow_console_input, GameAssets, GameState, GameplaySet, Locale};
+
+// Named players sharing one machine. Every profile keeps its own high scores, settings, achievements
+// and stats; the default one uses the top-level data folder, so files from before profiles still count.
+// Others live under profiles/<name>. P on the main menu opens the screen to pick, create or delete them.
+pub const DEFAULT_PROFILE: &str = "Player";
+const PROFILES_DIR: &str = "profiles";
+const CURRENT_PROFILE_FILE: &str = "profile.txt";
+const MAX_NAME_LENGTH: usize = 12;
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CurrentProfile(pub String);
+
+#[derive(Resource, Default)]
+struct ProfileScreen {
+    open: bool,
+    selected: usize,
+    profiles: Vec<String>,
+    // Some while a new name is being typed
+    new_name: Option<String>,
+}
+
+#[derive(Component)]
+struct ProfileScreenText;
+
+pub struct ProfilesPlugin;
+
+impl Plugin for ProfilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProfileScreen>()
+            .add_systems(Startup, setup_profile_ui)
+            .add_systems(PreUpdate, swallow_profile_input.after(InputSystem).before(swallow_console_input))
+            .add_systems(Update, profile_screen_input.run_if(in_state(GameState::MainMenu)).in_set(GameplaySet::Input))
+            .add_systems(Update, update_profile_text.in_set(GameplaySet::Ui))
+            .add_systems(OnExit(GameState::MainMenu), close_profile_screen);
+    }
+}
+
+impl CurrentProfile {
+    // The last profile used, as long as it hasn't been deleted since
+    pub fn load() -> Self {
+        let name = storage::read_to_string(&storage::config_file(CURRENT_PROFILE_FILE))
+            .map(|contents| contents.trim().to_string())
+            .unwrap_or_default();
+        if list_profiles().contains(&name) {
+            CurrentProfile(name)
+        } else {
+            CurrentProfile(DEFAULT_PROFILE.to_string())
+        }
+    }
+
+    fn save(&self) {
+        if let Err(error) = storage::write(&storage::config_file(CURRENT_PROFILE_FILE), &self.0) {
+            warn!("Could not save the current profile: {error}");
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_PROFILE
+    }
+
+    pub fn data_file(&self, name: &str) -> PathBuf {
+        if self.is_default() {
+            storage::data_file(name)
+        } else {
+            storage::data_file(&format!("{PROFILES_DIR}/{}/{name}", self.0))
+        }
+    }
+
+    pub fn config_file(&self, name: &str) -> PathBuf {
+        if self.is_default() {
+            storage::config_file(name)
+        } else {
+            storage::config_file(&format!("{PROFILES_DIR}/{}/{name}", self.0))
+        }
+    }
+}
+
+// The default profile first, then the rest alphabetically
+fn list_profiles() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(storage::data_file(PROFILES_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+fn create_profile(name: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(storage::data_file(&format!("{PROFILES_DIR}/{name}")))
+}
+
+fn delete_profile(name: &str) {
+    for dir in [storage::data_file(&format!("{PROFILES_DIR}/{name}")), storage::config_file(&format!("{PROFILES_DIR}/{name}"))] {
+        if let Err(error) = std::fs::remove_dir_all(&dir)
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Could not remove {}: {error}", dir.display());
+        }
+    }
+}
+
+// Folder names come straight from what was typed, so keep them to characters every OS accepts
+fn allowed_in_name(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '-' || character == '_'
+}
+
+fn switch_profile(profile: &mut CurrentProfile, name: &str) {
+    if profile.0 != name {
+        profile.0 = name.to_string();
+        profile.save();
+        println!("Switched to profile {name}");
+    }
+}
+
+fn setup_profile_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(25.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        ProfileScreenText,
+    ));
+}
+
+// Keeps the main menu from starting a run or changing rules while the screen is up
+fn swallow_profile_input(screen: Res<ProfileScreen>, mut keyboard_input: ResMut<ButtonInput<KeyCode>>) {
+    if screen.open {
+        keyboard_input.reset_all();
+    }
+}
+
+// Raw key events, so names can be typed
+fn profile_screen_input(
+    mut key_events: EventReader<KeyboardInput>,
+    mut screen: ResMut<ProfileScreen>,
+    mut profile: ResMut<CurrentProfile>,
+) {
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if !screen.open {
+            if event.key_code == KeyCode::KeyP {
+                let profiles = list_profiles();
+                screen.selected = profiles.iter().position(|name| *name == profile.0).unwrap_or(0);
+                screen.profiles = profiles;
+                screen.open = true;
+            }
+            continue;
+        }
+
+        if let Some(new_name) = screen.new_name.as_mut() {
+            match &event.logical_key {
+                Key::Enter => {
+                    let name = std::mem::take(new_name);
+                    screen.new_name = None;
+                    if name.is_empty() || screen.profiles.contains(&name) {
+                        continue;
+                    }
+                    match create_profile(&name) {
+                        Ok(()) => {
+                            switch_profile(&mut profile, &name);
+                            screen.profiles = list_profiles();
+                            screen.selected = screen.profiles.iter().position(|existing| *existing == name).unwrap_or(0);
+                        }
+                        Err(error) => warn!("Could not create profile {name}: {error}"),
+                    }
+                }
+                Key::Escape => screen.new_name = None,
+                Key::Backspace => {
+                    new_name.pop();
+                }
+                Key::Character(characters) => {
+                    for character in characters.chars().filter(|character| allowed_in_name(*character)) {
+                        if new_name.chars().count() < MAX_NAME_LENGTH {
+                            new_name.push(character);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let count = screen.profiles.len();
+        match event.key_code {
+            KeyCode::ArrowUp => screen.selected = (screen.selected + count - 1) % count,
+            KeyCode::ArrowDown => screen.selected = (screen.selected + 1) % count,
+            KeyCode::Enter => {
+                let name = screen.profiles[screen.selected].clone();
+                switch_profile(&mut profile, &name);
+                screen.open = false;
+            }
+            KeyCode::KeyN => screen.new_name = Some(String::new()),
+            // The default profile is the fallback for everything else, so it stays
+            KeyCode::Delete => {
+                let name = screen.profiles[screen.selected].clone();
+                if name == DEFAULT_PROFILE {
+                    continue;
+                }
+                delete_profile(&name);
+                if profile.0 == name {
+                    switch_profile(&mut profile, DEFAULT_PROFILE);
+                }
+                screen.profiles = list_profiles();
+                screen.selected = screen.selected.min(screen.profiles.len() - 1);
+                println!("Deleted profile {name}");
+            }
+            KeyCode::Escape | KeyCode::KeyP => screen.open = false,
+            _ => {}
+        }
+    }
+}
+
+fn close_profile_screen(mut screen: ResMut<ProfileScreen>) {
+    screen.open = false;
+    screen.new_name = None;
+}
+
+fn update_profile_text(
+    screen: Res<ProfileScreen>,
+    profile: Res<CurrentProfile>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ProfileScreenText>>,
+) {
+    if !screen.is_changed() && !profile.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if screen.open { Visibility::Inherited } else { Visibility::Hidden };
+        let mut lines = vec![locale.get("profiles.title").to_string()];
+        for (index, name) in screen.profiles.iter().enumerate() {
+            let cursor = if index == screen.selected { ">" } else { " " };
+            let active = if *name == profile.0 { locale.get("profiles.active") } else { "" };
+            lines.push(format!("{} {} {}", cursor, name, active));
+        }
+        match &screen.new_name {
+            Some(new_name) => lines.push(format!("{}{}_", locale.get("profiles.new"), new_name)),
+            None => lines.push(locale.get("profiles.hint").to_string()),
+        }
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    game_over, net, spawn_asteroid, spawn_power_up, versus_mode, GameAssets, GameRng, GameState, GameplaySet, GravityFlip, LaserBeam,
+    Lifetime, Locale, Palette, PowerUp, Ruleset, WaveScoped,
+};
+
+// Mid-wave events: every few seconds of a wave the director rolls the game RNG for a chance at a
+// meteor shower, a supply drop or an EMP, and announces it with a banner. Seeded runs (daily challenge,
+// the console's seed command) therefore get the same events at the same moments.
+const ROLL_INTERVAL: f32 = 10.0;
+const EVENT_CHANCE: f64 = 0.35;
+const METEOR_SHOWER_SECONDS: f32 = 5.0;
+const METEOR_INTERVAL: f32 = 0.35;
+const EMP_SECONDS: f32 = 3.0;
+const BANNER_SECONDS: f32 = 2.0;
+const SUPPLY_DROPS: [PowerUp; 4] = [PowerUp::WeaponUpgrade, PowerUp::Laser, PowerUp::SpreadShot, PowerUp::Pierce];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RandomEvent {
+    // Rocks rain down for a few seconds
+    MeteorShower,
+    // A guaranteed power-up falls from the top
+    SupplyDrop,
+    // Nobody can shoot for a few seconds, player or invaders
+    Emp,
+}
+
+#[derive(Resource)]
+pub struct EventDirector {
+    roll: Timer,
+    // The running event and how long it has left; a supply drop is over as soon as it falls
+    active: Option<(RandomEvent, Timer)>,
+    meteor: Timer,
+}
+
+pub struct RandomEventsPlugin;
+
+impl Plugin for RandomEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventDirector>()
+            .add_systems(OnEnter(GameState::LevelIntro), reset_event_director)
+            .add_systems(
+                Update,
+                (run_event_director, rain_meteors)
+                    .chain()
+                    .in_set(GameplaySet::Movement)
+                    .run_if(not(game_over))
+                    .run_if(not(versus_mode))
+                    .run_if(not(net::is_client))
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+impl Default for EventDirector {
+    fn default() -> Self {
+        EventDirector {
+            roll: Timer::from_seconds(ROLL_INTERVAL, TimerMode::Repeating),
+            active: None,
+            meteor: Timer::from_seconds(METEOR_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl RandomEvent {
+    const ALL: [RandomEvent; 3] = [RandomEvent::MeteorShower, RandomEvent::SupplyDrop, RandomEvent::Emp];
+
+    fn banner_key(self) -> &'static str {
+        match self {
+            RandomEvent::MeteorShower => "event.meteor_shower",
+            RandomEvent::SupplyDrop => "event.supply_drop",
+            RandomEvent::Emp => "event.emp",
+        }
+    }
+
+    fn seconds(self) -> f32 {
+        match self {
+            RandomEvent::MeteorShower => METEOR_SHOWER_SECONDS,
+            RandomEvent::SupplyDrop => 0.0,
+            RandomEvent::Emp => EMP_SECONDS,
+        }
+    }
+}
+
+// Run condition for everything that shoots
+pub fn emp_active(director: Res<EventDirector>) -> bool {
+    matches!(director.active, Some((RandomEvent::Emp, _)))
+}
+
+// Each wave starts calm, with no EMP left over from the last one
+fn reset_event_director(mut director: ResMut<EventDirector>) {
+    *director = EventDirector::default();
+}
+
+fn run_event_director(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<GameAssets>,
+    locale: Res<Locale>,
+    palette: Res<Palette>,
+    ruleset: Res<Ruleset>,
+    flip: Res<GravityFlip>,
+    windows: Query<&Window>,
+    beam_query: Query<Entity, With<LaserBeam>>,
+    mut rng: ResMut<GameRng>,
+    mut director: ResMut<EventDirector>,
+) {
+    // Events are part of the modern ruleset, like the power-ups a supply drop brings
+    if !ruleset.power_ups() {
+        return;
+    }
+    if let Some((event, timer)) = director.active.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            println!("{:?} is over", event);
+            director.active = None;
+        }
+        return;
+    }
+    if !director.roll.tick(time.delta()).just_finished() || !rng.0.random_bool(EVENT_CHANCE) {
+        return;
+    }
+    let event = RandomEvent::ALL[rng.0.random_range(0..RandomEvent::ALL.len())];
+    match event {
+        RandomEvent::SupplyDrop => {
+            let (half_width, top) = windows
+                .get_single()
+                .map(|window| (window.width() / 2.0 - 40.0, window.height() / 2.0))
+                .unwrap_or((300.0, 300.0));
+            let kind = SUPPLY_DROPS[rng.0.random_range(0..SUPPLY_DROPS.len())];
+            let x = rng.0.random_range(-half_width..half_width);
+            spawn_power_up(&mut commands, &palette, Vec3::new(x, flip.y(top), 0.0), kind);
+        }
+        RandomEvent::Emp => {
+            // A beam that is already on goes out with everything else
+            for entity in beam_query.iter() {
+                commands.entity(entity).despawn();
+            }
+        }
+        RandomEvent::MeteorShower => director.meteor.reset(),
+    }
+    director.active = Some((event, Timer::from_seconds(event.seconds(), TimerMode::Once)));
+    println!("Random event: {:?}", event);
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                locale.get(event.banner_key()),
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 48.0,
+                    color: Color::rgb(1.0, 0.85, 0.3),
+                },
+            )
+            .with_justify(JustifyText::Center),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(22.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        Lifetime(Timer::from_seconds(BANNER_SECONDS, TimerMode::Once)),
+        WaveScoped,
+    ));
+}
+
+fn rain_meteors(
+    mut commands: Commands,
+    time: Res<Time>,
+    palette: Res<Palette>,
+    flip: Res<GravityFlip>,
+    windows: Query<&Window>,
+    mut rng: ResMut<GameRng>,
+    mut director: ResMut<EventDirector>,
+) {
+    if !matches!(director.active, Some((RandomEvent::MeteorShower, _))) || !director.meteor.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    spawn_asteroid(&mut commands, &palette, window, *flip, &mut rng);
+}
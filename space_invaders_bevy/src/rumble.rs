@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+
+use super::{GameplaySet, Settings};
+
+// Gamepad force feedback for the big moments (getting hit, charged shots, boss phase changes),
+// sent as events like the hit flash and scaled or switched off by the rumble setting
+#[derive(Event)]
+pub struct Rumble {
+    pub strength: f32,
+    pub seconds: f32,
+}
+
+pub struct RumblePlugin;
+
+impl Plugin for RumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Rumble>().add_systems(Update, apply_rumble.in_set(GameplaySet::Ui));
+    }
+}
+
+fn apply_rumble(
+    mut rumble_events: EventReader<Rumble>,
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let scale = settings.rumble.factor();
+    for rumble in rumble_events.read() {
+        if scale == 0.0 {
+            continue;
+        }
+        let strength = (rumble.strength * scale).min(1.0);
+        // The weak motor carries the buzz on light hits, the strong one kicks in as strength grows
+        let intensity = GamepadRumbleIntensity {
+            strong_motor: strength * strength,
+            weak_motor: strength,
+        };
+        for gamepad in gamepads.iter() {
+            rumble_requests.send(GamepadRumbleRequest::Add {
+                gamepad,
+                intensity,
+                duration: Duration::from_secs_f32(rumble.seconds),
+            });
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::input::{ActionState, PlayerAction};
+use super::profiles::CurrentProfile;
+use super::{
+    storage, DespawnOutside, EnemyKilled, GameAssets, GameOver, GameRng, GameState, GameplaySet, GravityFlip, Locale, Localized, Palette,
+    Player, PlayerLives, QuitDialog, Ruleset, WaveScoped, WaveWon, OFFSCREEN_MARGIN,
+};
+
+// Scrap: the currency for buying upgrades. Destroyed invaders now and then drop a bit that falls
+// like a power-up and is picked up by touching it. It goes into the profile's scrap.ron and never
+// into the score, so spending it costs nothing on the leaderboards. For now the only thing on sale
+// is a spare life, bought on the wave cleared screen.
+const SCRAP_FILE: &str = "scrap.ron";
+const SCRAP_DROP_CHANCE: f64 = 0.3;
+// Elites always drop, and drop more
+const ELITE_SCRAP: u32 = 3;
+const SCRAP_FALL_SPEED: f32 = 90.0;
+const SCRAP_SIZE: f32 = 10.0;
+const LIFE_PRICE: u32 = 25;
+
+#[derive(Component)]
+pub struct Scrap(u32);
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct ScrapWallet {
+    pub total: u32,
+}
+
+#[derive(Component)]
+struct ScrapText;
+
+#[derive(Component)]
+struct ShopText;
+
+pub struct ScrapPlugin;
+
+impl Plugin for ScrapPlugin {
+    fn build(&self, app: &mut App) {
+        let wallet = ScrapWallet::load(app.world.resource::<CurrentProfile>());
+        app.insert_resource(wallet)
+            .add_systems(Startup, (setup_scrap_ui, setup_shop_ui))
+            .add_systems(Update, buy_life.in_set(GameplaySet::Input).run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                (drop_scrap, scrap_movement, collect_scrap, save_scrap_after_wave)
+                    .chain()
+                    .in_set(GameplaySet::Cleanup),
+            )
+            .add_systems(Update, (switch_scrap_profile, update_scrap_text, update_shop_text).chain().in_set(GameplaySet::Ui))
+            .add_systems(OnEnter(GameState::MainMenu), save_scrap)
+            .add_systems(Last, save_scrap_on_exit);
+    }
+}
+
+impl ScrapWallet {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(SCRAP_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(SCRAP_FILE), self, "scrap");
+    }
+}
+
+// Same rules as power-ups: classic games have none
+fn drop_scrap(
+    mut commands: Commands,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut rng: ResMut<GameRng>,
+    palette: Res<Palette>,
+    ruleset: Res<Ruleset>,
+) {
+    for killed in killed_events.read() {
+        if !ruleset.power_ups() {
+            continue;
+        }
+        let amount = if killed.elite {
+            ELITE_SCRAP
+        } else if rng.0.random_bool(SCRAP_DROP_CHANCE) {
+            1
+        } else {
+            continue;
+        };
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: palette.scrap,
+                    custom_size: Some(Vec2::splat(SCRAP_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(killed.position),
+                ..default()
+            },
+            Scrap(amount),
+            DespawnOutside(OFFSCREEN_MARGIN),
+            WaveScoped,
+        ));
+    }
+}
+
+fn scrap_movement(mut query: Query<&mut Transform, With<Scrap>>, time: Res<Time>, flip: Res<GravityFlip>) {
+    for mut transform in query.iter_mut() {
+        transform.translation.y -= flip.y(SCRAP_FALL_SPEED * time.delta_seconds());
+    }
+}
+
+fn collect_scrap(
+    mut commands: Commands,
+    scrap_query: Query<(Entity, &Transform, &Scrap)>,
+    player_query: Query<(&Transform, &Sprite), With<Player>>,
+    mut wallet: ResMut<ScrapWallet>,
+) {
+    let Ok((player_tf, player_sprite)) = player_query.get_single() else {
+        return;
+    };
+    let reach = player_sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0 + Vec2::splat(SCRAP_SIZE / 2.0);
+    for (entity, scrap_tf, scrap) in scrap_query.iter() {
+        let distance = (scrap_tf.translation - player_tf.translation).truncate().abs();
+        if distance.x < reach.x && distance.y < reach.y {
+            commands.entity(entity).despawn();
+            wallet.total += scrap.0;
+            println!("Picked up {} scrap, {} total", scrap.0, wallet.total);
+        }
+    }
+}
+
+fn buy_life(
+    action_state: Res<ActionState>,
+    wave_won: Res<WaveWon>,
+    quit_dialog: Res<QuitDialog>,
+    profile: Res<CurrentProfile>,
+    mut wallet: ResMut<ScrapWallet>,
+    mut lives: ResMut<PlayerLives>,
+) {
+    if !wave_won.0 || quit_dialog.open || !action_state.just_pressed(PlayerAction::BuyLife) {
+        return;
+    }
+    if wallet.total < LIFE_PRICE {
+        println!("Not enough scrap for a life: {} of {}", wallet.total, LIFE_PRICE);
+        return;
+    }
+    wallet.total -= LIFE_PRICE;
+    lives.0 += 1;
+    wallet.save(&profile);
+    println!("Bought a life for {} scrap, {} left", LIFE_PRICE, wallet.total);
+}
+
+// Written whenever a wave ends, won or lost, rather than on every pickup
+fn save_scrap_after_wave(game_over: Res<GameOver>, profile: Res<CurrentProfile>, wallet: Res<ScrapWallet>) {
+    if game_over.is_changed() && game_over.0 {
+        wallet.save(&profile);
+    }
+}
+
+fn save_scrap(profile: Res<CurrentProfile>, wallet: Res<ScrapWallet>) {
+    wallet.save(&profile);
+}
+
+fn save_scrap_on_exit(mut exit_events: EventReader<AppExit>, profile: Res<CurrentProfile>, wallet: Res<ScrapWallet>) {
+    if exit_events.read().count() > 0 {
+        wallet.save(&profile);
+    }
+}
+
+fn switch_scrap_profile(profile: Res<CurrentProfile>, mut wallet: ResMut<ScrapWallet>) {
+    if profile.is_changed() && !profile.is_added() {
+        *wallet = ScrapWallet::load(&profile);
+    }
+}
+
+fn setup_scrap_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Scrap: ",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(190.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ScrapText,
+        Localized("hud.scrap"),
+    ));
+}
+
+fn update_scrap_text(wallet: Res<ScrapWallet>, mut query: Query<&mut Text, With<ScrapText>>) {
+    if wallet.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[1].value = wallet.total.to_string();
+        }
+    }
+}
+
+fn setup_shop_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 24.0,
+                color: Color::rgb(0.75, 0.75, 0.8),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(250.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ShopText,
+    ));
+}
+
+// Only offered while it can be afforded
+fn update_shop_text(
+    wave_won: Res<WaveWon>,
+    wallet: Res<ScrapWallet>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<ShopText>>,
+) {
+    let label = if wave_won.0 && wallet.total >= LIFE_PRICE {
+        format!("{}{}", locale.get("scrap.buy_life"), LIFE_PRICE)
+    } else {
+        String::new()
+    };
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use image::RgbImage;
+
+use super::{GameAssets, GameplaySet, Locale};
+
+// F12 saves the current frame as a timestamped PNG under screenshots/.
+// F9 exports the last CLIP_SECONDS as a numbered PNG sequence under clips/.
+const SCREENSHOT_DIR: &str = "screenshots";
+const CLIP_DIR: &str = "clips";
+const CLIP_SECONDS: f32 = 30.0;
+const CLIP_FPS: f32 = 10.0;
+// Frames are shrunk by this factor so the rolling buffer stays a few dozen MB
+const CLIP_DOWNSCALE: u32 = 4;
+const TOAST_SECONDS: f32 = 2.0;
+
+#[derive(Component)]
+struct ScreenshotToast {
+    timer: Timer,
+}
+
+// Shared with the screenshot callbacks, which run on the render side
+#[derive(Resource)]
+struct ClipBuffer {
+    frames: Arc<Mutex<VecDeque<RgbImage>>>,
+    capture: Timer,
+}
+
+pub struct ScreenshotsPlugin;
+
+impl Plugin for ScreenshotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClipBuffer {
+            frames: Arc::new(Mutex::new(VecDeque::new())),
+            capture: Timer::from_seconds(1.0 / CLIP_FPS, TimerMode::Repeating),
+        })
+        .add_systems(
+            Update,
+            (
+                take_screenshot.in_set(GameplaySet::Input),
+                export_clip.in_set(GameplaySet::Input),
+                capture_clip_frame.in_set(GameplaySet::Cleanup),
+                fade_screenshot_toasts.in_set(GameplaySet::Ui),
+            ),
+        );
+    }
+}
+
+fn timestamp_millis() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis()).unwrap_or_default()
+}
+
+fn take_screenshot(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    // Missing when nothing is rendered, e.g. in headless runs
+    screenshot_manager: Option<ResMut<ScreenshotManager>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    locale: Res<Locale>,
+    assets: Res<GameAssets>,
+    toast_query: Query<Entity, With<ScreenshotToast>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let (Some(mut screenshot_manager), Ok(window)) = (screenshot_manager, window_query.get_single()) else {
+        return;
+    };
+    if let Err(error) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        warn!("Could not create {SCREENSHOT_DIR}: {error}");
+        return;
+    }
+    let path = format!("{SCREENSHOT_DIR}/screenshot-{}.png", timestamp_millis());
+    if let Err(error) = screenshot_manager.save_screenshot_to_disk(window, &path) {
+        warn!("Could not take screenshot: {error}");
+        return;
+    }
+    println!("Screenshot saved to {path}");
+    show_toast(&mut commands, &assets, &toast_query, format!("{} {}", locale.get("screenshot.saved"), path));
+}
+
+// Keeps a low-rate, downscaled copy of the last CLIP_SECONDS of frames
+fn capture_clip_frame(
+    time: Res<Time<Real>>,
+    mut buffer: ResMut<ClipBuffer>,
+    screenshot_manager: Option<ResMut<ScreenshotManager>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !buffer.capture.tick(time.delta()).just_finished() {
+        return;
+    }
+    let (Some(mut screenshot_manager), Ok(window)) = (screenshot_manager, window_query.get_single()) else {
+        return;
+    };
+    let frames = buffer.frames.clone();
+    // Fails when F12 already asked for this frame; the clip just skips it
+    let _ = screenshot_manager.take_screenshot(window, move |image| {
+        let Ok(image) = image.try_into_dynamic() else {
+            return;
+        };
+        let frame = image
+            .thumbnail(image.width() / CLIP_DOWNSCALE, image.height() / CLIP_DOWNSCALE)
+            .to_rgb8();
+        let mut frames = frames.lock().unwrap();
+        frames.push_back(frame);
+        while frames.len() > (CLIP_SECONDS * CLIP_FPS) as usize {
+            frames.pop_front();
+        }
+    });
+}
+
+fn export_clip(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    buffer: Res<ClipBuffer>,
+    locale: Res<Locale>,
+    assets: Res<GameAssets>,
+    toast_query: Query<Entity, With<ScreenshotToast>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let frames: Vec<RgbImage> = buffer.frames.lock().unwrap().iter().cloned().collect();
+    if frames.is_empty() {
+        return;
+    }
+    let dir = format!("{CLIP_DIR}/clip-{}", timestamp_millis());
+    if let Err(error) = std::fs::create_dir_all(&dir) {
+        warn!("Could not create {dir}: {error}");
+        return;
+    }
+    // Encoding a few hundred PNGs takes a moment, so it happens off the main thread
+    let frame_count = frames.len();
+    let out_dir = dir.clone();
+    std::thread::spawn(move || {
+        for (index, frame) in frames.iter().enumerate() {
+            if let Err(error) = frame.save(format!("{out_dir}/frame-{index:04}.png")) {
+                warn!("Could not save clip frame: {error}");
+                return;
+            }
+        }
+        println!("Clip of {frame_count} frames saved to {out_dir}");
+    });
+    show_toast(&mut commands, &assets, &toast_query, format!("{} {}", locale.get("clip.saved"), dir));
+}
+
+fn show_toast(commands: &mut Commands, assets: &GameAssets, toast_query: &Query<Entity, With<ScreenshotToast>>, message: String) {
+    // Only the latest confirmation stays up
+    for toast in toast_query.iter() {
+        commands.entity(toast).despawn();
+    }
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                message,
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            z_index: ZIndex::Global(25),
+            ..default()
+        },
+        ScreenshotToast {
+            timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+        },
+    ));
+}
+
+// Real time, so the toast still clears while the game is paused
+fn fade_screenshot_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut Text, &mut ScreenshotToast)>,
+) {
+    for (entity, mut text, mut toast) in query.iter_mut() {
+        toast.timer.tick(time.delta());
+        let remaining = toast.timer.fraction_remaining();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(remaining);
+        }
+        if toast.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
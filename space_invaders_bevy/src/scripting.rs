@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rhai::{Dynamic, Engine, EvalAltResult, FuncArgs, Scope, AST};
+
+use super::{game_over, mods, Diving, Enemy, FirePattern, FlyingIn, FormationSlot, GameState, GameplaySet, Level, LevelLayout};
+
+// Rows listed under `scripts` in the level file get a rhai script that can override
+// their fire pattern (`fire_pattern(level)`) and add sideways sway (`movement(t)`).
+pub struct ScriptingPlugin;
+
+#[derive(Component)]
+struct ScriptedEnemy {
+    script: String,
+}
+
+#[derive(Resource)]
+struct ScriptEngine {
+    engine: Engine,
+    // Failed compiles are cached as None so a broken script only warns once
+    compiled: HashMap<String, Option<AST>>,
+}
+
+impl ScriptEngine {
+    fn call<T: Clone + Send + Sync + 'static>(&mut self, script: &str, function: &str, args: impl FuncArgs) -> Option<T> {
+        if !self.compiled.contains_key(script) {
+            let ast = match mods::read_data_to_string(script) {
+                Ok(source) => match self.engine.compile(source) {
+                    Ok(ast) => Some(ast),
+                    Err(error) => {
+                        warn!("Could not compile {script}: {error}");
+                        None
+                    }
+                },
+                Err(error) => {
+                    warn!("Could not read {script}: {error}");
+                    None
+                }
+            };
+            self.compiled.insert(script.to_string(), ast);
+        }
+        let ast = self.compiled.get(script)?.as_ref()?;
+        match self.engine.call_fn::<T>(&mut Scope::new(), ast, function, args) {
+            Ok(value) => Some(value),
+            // Scripts only define the hooks they care about
+            Err(error) if matches!(*error, EvalAltResult::ErrorFunctionNotFound(..)) => None,
+            Err(error) => {
+                warn!("{script}: {function} failed: {error}");
+                None
+            }
+        }
+    }
+}
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScriptEngine { engine: Engine::new(), compiled: HashMap::new() })
+            .add_systems(
+                Update,
+                (attach_scripts, apply_scripted_fire_patterns, apply_scripted_movement)
+                    .chain()
+                    .in_set(GameplaySet::Movement)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(game_over)),
+            );
+    }
+}
+
+fn parse_fire_pattern(name: &str) -> Option<FirePattern> {
+    match name {
+        "single" => Some(FirePattern::Single),
+        "spread" => Some(FirePattern::Spread),
+        "burst" => Some(FirePattern::Burst),
+        "aimed" => Some(FirePattern::Aimed),
+        _ => None,
+    }
+}
+
+fn attach_scripts(
+    mut commands: Commands,
+    layout: Res<LevelLayout>,
+    mut engine: ResMut<ScriptEngine>,
+    enemy_query: Query<(Entity, &FormationSlot), Added<Enemy>>,
+) {
+    // An edited level file may point at new or changed scripts
+    if layout.is_changed() {
+        engine.compiled.clear();
+    }
+    for (entity, slot) in enemy_query.iter() {
+        if let Some(script) = layout.scripts.get(&slot.row) {
+            commands.entity(entity).insert(ScriptedEnemy { script: script.clone() });
+        }
+    }
+}
+
+fn apply_scripted_fire_patterns(
+    level: Res<Level>,
+    mut engine: ResMut<ScriptEngine>,
+    mut enemy_query: Query<(Ref<ScriptedEnemy>, &mut FirePattern)>,
+) {
+    let mut patterns: HashMap<String, Option<FirePattern>> = HashMap::new();
+    for (scripted, mut pattern) in enemy_query.iter_mut() {
+        if !level.is_changed() && !scripted.is_added() {
+            continue;
+        }
+        let chosen = patterns.entry(scripted.script.clone()).or_insert_with(|| {
+            engine
+                .call::<String>(&scripted.script, "fire_pattern", (level.0 as i64,))
+                .and_then(|name| parse_fire_pattern(&name))
+        });
+        if let Some(chosen) = chosen {
+            *pattern = *chosen;
+        }
+    }
+}
+
+fn apply_scripted_movement(
+    time: Res<Time>,
+    level: Res<Level>,
+    mut wave_clock: Local<f32>,
+    mut engine: ResMut<ScriptEngine>,
+    mut enemy_query: Query<(&ScriptedEnemy, &mut Transform), (Without<Diving>, Without<FlyingIn>)>,
+) {
+    if level.is_changed() {
+        *wave_clock = 0.0;
+    }
+    *wave_clock += time.delta_seconds();
+    let mut speeds: HashMap<String, Option<f32>> = HashMap::new();
+    for (scripted, mut transform) in enemy_query.iter_mut() {
+        let speed = speeds.entry(scripted.script.clone()).or_insert_with(|| {
+            engine
+                .call::<Dynamic>(&scripted.script, "movement", (*wave_clock as f64,))
+                .and_then(|value| value.as_float().ok())
+                .map(|value| value as f32)
+        });
+        if let Some(speed) = speed {
+            transform.translation.x += *speed * time.delta_seconds();
+        }
+    }
+}
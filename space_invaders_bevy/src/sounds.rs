@@ -0,0 +1,257 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use super::{
+    is_boss_level, mods, play_panned_sound, GameAssets, GameOver, GameState, GameplaySet, Level, Player, Ruleset, ShotFired, WaveWon,
+};
+
+// Sound effects come from assets/sounds when the file is there. Missing ones are synthesized
+// at startup as simple square and noise blips, so audio still works with an empty sounds folder.
+// Music works the same way from assets/music, crossfading whenever the situation calls for another track.
+const SAMPLE_RATE: u32 = 22050;
+const MUSIC_VOLUME: f32 = 0.4;
+const MUSIC_FADE_SECONDS: f32 = 1.0;
+
+// Part of GameAssets
+pub struct SoundEffects {
+    pub shoot: Handle<AudioSource>,
+    pub explosion: Handle<AudioSource>,
+    pub clink: Handle<AudioSource>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MusicTrack {
+    Menu,
+    Gameplay,
+    Boss,
+    GameOver,
+}
+
+impl MusicTrack {
+    const ALL: [MusicTrack; 4] = [MusicTrack::Menu, MusicTrack::Gameplay, MusicTrack::Boss, MusicTrack::GameOver];
+
+    fn file(self) -> &'static str {
+        match self {
+            MusicTrack::Menu => "menu.wav",
+            MusicTrack::Gameplay => "gameplay.wav",
+            MusicTrack::Boss => "boss.wav",
+            MusicTrack::GameOver => "game_over.wav",
+        }
+    }
+
+    // Square-note loop used when the music file is missing
+    fn synthesize(self) -> Vec<f32> {
+        match self {
+            MusicTrack::Menu => melody(&[262.0, 330.0, 392.0, 523.0, 392.0, 330.0], 0.4),
+            // The classic four-note march
+            MusicTrack::Gameplay => melody(&[98.0, 87.0, 78.0, 73.0], 0.5),
+            MusicTrack::Boss => melody(&[110.0, 131.0, 110.0, 165.0, 110.0, 156.0], 0.2),
+            MusicTrack::GameOver => melody(&[392.0, 349.0, 311.0, 262.0], 0.6),
+        }
+    }
+}
+
+// Owns the playing music entities: the one fading in (or fully up) and any still fading out
+#[derive(Resource, Default)]
+struct MusicController {
+    tracks: Vec<(MusicTrack, Handle<AudioSource>)>,
+    current: Option<(MusicTrack, Entity, Timer)>,
+    fading_out: Vec<(Entity, Timer)>,
+}
+
+pub struct SoundsPlugin;
+
+impl Plugin for SoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicController>()
+            .add_systems(Startup, load_music)
+            .add_systems(
+                Update,
+                (
+                    play_shot_sound,
+                    (select_music_track, update_music_fades).chain(),
+                )
+                    .in_set(GameplaySet::Ui),
+            );
+    }
+}
+
+impl FromWorld for SoundEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>().clone();
+        let mut sources = world.resource_mut::<Assets<AudioSource>>();
+        SoundEffects {
+            shoot: load_or_synthesize(&asset_server, &mut sources, "sounds/shoot.wav".into(), &|| square_sweep(880.0, 440.0, 0.12)),
+            explosion: load_or_synthesize(&asset_server, &mut sources, "sounds/explosion.wav".into(), &|| noise_burst(0.35)),
+            clink: load_or_synthesize(&asset_server, &mut sources, "sounds/clink.wav".into(), &|| square_sweep(1800.0, 1800.0, 0.05)),
+        }
+    }
+}
+
+fn load_or_synthesize(
+    asset_server: &AssetServer,
+    sources: &mut Assets<AudioSource>,
+    path: String,
+    synthesize: &dyn Fn() -> Vec<f32>,
+) -> Handle<AudioSource> {
+    if mods::data_exists(&path) {
+        asset_server.load(path)
+    } else {
+        info!("{path} is missing, using a synthesized sound");
+        sources.add(AudioSource { bytes: wav_bytes(&synthesize()).into() })
+    }
+}
+
+fn load_music(asset_server: Res<AssetServer>, mut sources: ResMut<Assets<AudioSource>>, mut music: ResMut<MusicController>) {
+    music.tracks = MusicTrack::ALL
+        .iter()
+        .map(|&track| {
+            let path = format!("music/{}", track.file());
+            (track, load_or_synthesize(&asset_server, &mut sources, path, &|| track.synthesize()))
+        })
+        .collect();
+}
+
+// One blip per frame, however many bullets a spread shot put out
+fn play_shot_sound(
+    mut commands: Commands,
+    mut fired_events: EventReader<ShotFired>,
+    assets: Res<GameAssets>,
+    player_query: Query<&Transform, With<Player>>,
+    windows: Query<&Window>,
+) {
+    if fired_events.read().count() == 0 {
+        return;
+    }
+    let (Ok(player_tf), Ok(window)) = (player_query.get_single(), windows.get_single()) else {
+        return;
+    };
+    play_panned_sound(&mut commands, assets.sounds.shoot.clone(), player_tf.translation.x, window);
+}
+
+fn select_music_track(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    level: Res<Level>,
+    game_over: Res<GameOver>,
+    wave_won: Res<WaveWon>,
+    ruleset: Res<Ruleset>,
+    mut music: ResMut<MusicController>,
+) {
+    let wanted = match state.get() {
+        GameState::Loading | GameState::MainMenu => MusicTrack::Menu,
+        _ if game_over.0 && !wave_won.0 => MusicTrack::GameOver,
+        _ if is_boss_level(*ruleset, level.0) => MusicTrack::Boss,
+        _ => MusicTrack::Gameplay,
+    };
+    if music.current.as_ref().is_some_and(|(track, _, _)| *track == wanted) {
+        return;
+    }
+    let Some(source) = music.tracks.iter().find(|(track, _)| *track == wanted).map(|(_, source)| source.clone()) else {
+        return;
+    };
+    if let Some((_, entity, _)) = music.current.take() {
+        music.fading_out.push((entity, Timer::from_seconds(MUSIC_FADE_SECONDS, TimerMode::Once)));
+    }
+    let entity = commands
+        .spawn(AudioBundle {
+            source,
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+        })
+        .id();
+    music.current = Some((wanted, entity, Timer::from_seconds(MUSIC_FADE_SECONDS, TimerMode::Once)));
+    println!("Music: {:?}", wanted);
+}
+
+// Real time, so fades carry on through pauses and slow motion
+fn update_music_fades(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut music: ResMut<MusicController>,
+    sink_query: Query<&AudioSink>,
+) {
+    if let Some((_, entity, fade_in)) = music.current.as_mut() {
+        fade_in.tick(time.delta());
+        if let Ok(sink) = sink_query.get(*entity) {
+            sink.set_volume(fade_in.fraction() * MUSIC_VOLUME);
+        }
+    }
+    music.fading_out.retain_mut(|(entity, fade_out)| {
+        fade_out.tick(time.delta());
+        if fade_out.finished() {
+            commands.entity(*entity).despawn();
+            return false;
+        }
+        if let Ok(sink) = sink_query.get(*entity) {
+            sink.set_volume(fade_out.fraction_remaining() * MUSIC_VOLUME);
+        }
+        true
+    });
+}
+
+// Short square notes with a gap between them, meant to be looped
+fn melody(notes: &[f32], note_seconds: f32) -> Vec<f32> {
+    notes
+        .iter()
+        .flat_map(|&hz| {
+            let mut note = square_sweep(hz, hz, note_seconds * 0.7);
+            note.resize((note_seconds * SAMPLE_RATE as f32) as usize, 0.0);
+            note
+        })
+        .collect()
+}
+
+// Square wave gliding from one pitch to another, fading out linearly
+fn square_sweep(from_hz: f32, to_hz: f32, seconds: f32) -> Vec<f32> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0;
+    (0..count)
+        .map(|index| {
+            let progress = index as f32 / count as f32;
+            phase = (phase + (from_hz + (to_hz - from_hz) * progress) / SAMPLE_RATE as f32).fract();
+            let square = if phase < 0.5 { 1.0 } else { -1.0 };
+            square * 0.3 * (1.0 - progress)
+        })
+        .collect()
+}
+
+// Low-passed white noise with a fast exponential decay
+fn noise_burst(seconds: f32) -> Vec<f32> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    // Fixed xorshift seed: the sound is the same every run and leaves the game RNG alone
+    let mut state: u32 = 0x9E37_79B9;
+    let mut filtered = 0.0;
+    (0..count)
+        .map(|index| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let white = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            filtered = filtered * 0.7 + white * 0.3;
+            let t = index as f32 / SAMPLE_RATE as f32;
+            (filtered * 1.8 * (-t * 9.0).exp()).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+// 16-bit mono PCM in a WAV container, which the `wav` decoder already handles
+fn wav_bytes(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    bytes
+}
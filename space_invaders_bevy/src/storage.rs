@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use bevy::log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Where saves and preferences live: the per-user data and config folders of the OS
+// (XDG on Linux, AppData on Windows, Application Support on macOS) instead of the working directory.
+// Falls back to a folder in the working directory when none of the usual variables are set.
+const APP_DIR: &str = "space_invaders_bevy";
+
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().join(APP_DIR).join(name)
+}
+
+pub fn config_file(name: &str) -> PathBuf {
+    config_dir().join(APP_DIR).join(name)
+}
+
+// Files written by older builds next to the game are still picked up until the first save
+pub fn read_to_string(path: &Path) -> std::io::Result<String> {
+    std::fs::read_to_string(path).or_else(|error| match path.file_name() {
+        Some(name) if is_top_level(path) => std::fs::read_to_string(name),
+        _ => Err(error),
+    })
+}
+
+// Directly in the app folder rather than in a profile's subfolder
+fn is_top_level(path: &Path) -> bool {
+    path.parent()
+        .is_some_and(|parent| parent == data_dir().join(APP_DIR) || parent == config_dir().join(APP_DIR))
+}
+
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+}
+
+// A missing or unreadable file just means nothing has been saved yet
+pub fn load_ron<T: DeserializeOwned + Default>(path: &Path) -> T {
+    read_to_string(path)
+        .ok()
+        .and_then(|contents| ron::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// `what` names the data in the warning logged when it can't be written
+pub fn save_ron<T: Serialize>(path: &Path, value: &T, what: &str) {
+    match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => {
+            if let Err(error) = write(path, contents) {
+                warn!("Could not save {what}: {error}");
+            }
+        }
+        Err(error) => warn!("Could not serialize {what}: {error}"),
+    }
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn home_join(relative: &str) -> Option<PathBuf> {
+    env_dir("HOME").map(|home| home.join(relative))
+}
+
+#[cfg(target_os = "windows")]
+fn data_dir() -> PathBuf {
+    env_dir("APPDATA").unwrap_or_default()
+}
+
+#[cfg(target_os = "windows")]
+fn config_dir() -> PathBuf {
+    env_dir("APPDATA").unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn data_dir() -> PathBuf {
+    home_join("Library/Application Support").unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> PathBuf {
+    home_join("Library/Application Support").unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn data_dir() -> PathBuf {
+    env_dir("XDG_DATA_HOME").or_else(|| home_join(".local/share")).unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn config_dir() -> PathBuf {
+    env_dir("XDG_CONFIG_HOME").or_else(|| home_join(".config")).unwrap_or_default()
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use super::{EnemyKilled, EnemyShootTimer, EnemySpeed, GameAssets, GameConfig, GameMode, GameOver, GameplaySet, Locale, RestartRun, WaveClock};
+
+// Endless mode (--survival): the first wave never ends. The wave director keeps sending rows on a
+// timer that shortens as the run goes on, the invaders march and shoot faster by the minute,
+// and the run is measured in time survived and kills.
+const ROW_INTERVAL: f32 = 8.0;
+const MIN_ROW_INTERVAL: f32 = 2.0;
+// Seconds for the invaders' speed and fire rate to double
+const RAMP_SECONDS: f32 = 90.0;
+const MIN_SHOOT_COOLDOWN: f32 = 0.15;
+// Rows wait while the board is this full
+pub const MAX_ENEMIES: usize = 60;
+
+#[derive(Resource, Default)]
+pub struct SurvivalRun {
+    pub kills: u32,
+}
+
+#[derive(Component)]
+struct SurvivalText;
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SurvivalRun>()
+            .add_systems(Startup, setup_survival_ui.run_if(survival_mode))
+            .add_systems(
+                Update,
+                (reset_survival_run, count_survival_kills, ramp_aggression.run_if(not(super::game_over)))
+                    .chain()
+                    .in_set(GameplaySet::Cleanup)
+                    .run_if(survival_mode),
+            )
+            .add_systems(Update, update_survival_text.in_set(GameplaySet::Ui).run_if(survival_mode));
+    }
+}
+
+pub fn survival_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Survival
+}
+
+// How hard the invaders push, 1.0 at the start of the run
+pub fn aggression(elapsed: f32) -> f32 {
+    1.0 + elapsed / RAMP_SECONDS
+}
+
+// Seconds until the director sends the next row
+pub fn row_interval(elapsed: f32) -> f32 {
+    (ROW_INTERVAL / aggression(elapsed)).max(MIN_ROW_INTERVAL)
+}
+
+fn reset_survival_run(mut restart_events: EventReader<RestartRun>, mut run: ResMut<SurvivalRun>) {
+    if restart_events.read().count() > 0 {
+        *run = SurvivalRun::default();
+    }
+}
+
+fn count_survival_kills(mut killed_events: EventReader<EnemyKilled>, mut run: ResMut<SurvivalRun>) {
+    let kills = killed_events.read().count() as u32;
+    if kills > 0 {
+        run.kills += kills;
+    }
+}
+
+fn ramp_aggression(
+    clock: Res<WaveClock>,
+    config: Res<GameConfig>,
+    mut enemy_speed: ResMut<EnemySpeed>,
+    mut shoot_timer: ResMut<EnemyShootTimer>,
+) {
+    let aggression = aggression(clock.elapsed);
+    enemy_speed.0 = config.enemy_speed * aggression;
+    let cooldown = (config.enemy_shoot_cooldown / aggression).max(MIN_SHOOT_COOLDOWN);
+    shoot_timer.0.set_duration(std::time::Duration::from_secs_f32(cooldown));
+}
+
+fn setup_survival_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(160.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        SurvivalText,
+    ));
+}
+
+fn update_survival_text(
+    clock: Res<WaveClock>,
+    run: Res<SurvivalRun>,
+    locale: Res<Locale>,
+    game_over: Res<GameOver>,
+    mut query: Query<&mut Text, With<SurvivalText>>,
+) {
+    if !clock.is_changed() && !run.is_changed() && !locale.is_changed() && !game_over.is_changed() {
+        return;
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].value = survival_summary(&locale, clock.elapsed, run.kills);
+    }
+}
+
+pub fn survival_summary(locale: &Locale, elapsed: f32, kills: u32) -> String {
+    let seconds = elapsed as u32;
+    format!(
+        "{} {}:{:02}  {} {}",
+        locale.get("survival.time"),
+        seconds / 60,
+        seconds % 60,
+        locale.get("survival.kills"),
+        kills
+    )
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use super::{EnemyKilled, GameplaySet, Level, PlayerLives, PowerUpCollected, RestartRun, RunFinished, WaveCleared};
+
+// Opt-in (--telemetry DIR) JSON log of each finished run for balance tuning.
+// Event times are seconds of game time since the run started.
+pub struct TelemetryPlugin {
+    pub dir: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TelemetryEvent {
+    Kill { t: f32, level: u32, elite: bool, charged: bool },
+    Death { t: f32, level: u32, lives_left: u32 },
+    PowerUp { t: f32, level: u32, power_up: String },
+    WaveCleared { t: f32, level: u32, duration: f32 },
+}
+
+#[derive(Serialize)]
+struct RunReport<'a> {
+    finished_at: u64,
+    score: u32,
+    level: u32,
+    reduced_speed: bool,
+    duration: f32,
+    events: &'a [TelemetryEvent],
+}
+
+#[derive(Resource)]
+struct RunLog {
+    dir: PathBuf,
+    started_at: f32,
+    wave_started_at: f32,
+    last_lives: u32,
+    events: Vec<TelemetryEvent>,
+}
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        if let Err(error) = std::fs::create_dir_all(&self.dir) {
+            warn!("Could not create telemetry folder {}: {error}", self.dir.display());
+        }
+        app.insert_resource(RunLog {
+            dir: self.dir.clone(),
+            started_at: 0.0,
+            wave_started_at: 0.0,
+            last_lives: 0,
+            events: Vec::new(),
+        })
+        .add_systems(Update, (record_run_events, write_run_report).chain().in_set(GameplaySet::Cleanup));
+    }
+}
+
+fn record_run_events(
+    time: Res<Time>,
+    level: Res<Level>,
+    lives: Res<PlayerLives>,
+    mut restart_events: EventReader<RestartRun>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut collected_events: EventReader<PowerUpCollected>,
+    mut cleared_events: EventReader<WaveCleared>,
+    mut log: ResMut<RunLog>,
+) {
+    let now = time.elapsed_seconds();
+    if restart_events.read().count() > 0 {
+        log.started_at = now;
+        log.events.clear();
+    }
+    if level.is_changed() {
+        log.wave_started_at = now;
+    }
+    let t = now - log.started_at;
+    for kill in killed_events.read() {
+        log.events.push(TelemetryEvent::Kill { t, level: level.0, elite: kill.elite, charged: kill.charged });
+    }
+    for PowerUpCollected(power_up) in collected_events.read() {
+        log.events.push(TelemetryEvent::PowerUp { t, level: level.0, power_up: format!("{:?}", power_up) });
+    }
+    if cleared_events.read().count() > 0 {
+        let duration = now - log.wave_started_at;
+        log.events.push(TelemetryEvent::WaveCleared { t, level: level.0, duration });
+    }
+    if lives.0 < log.last_lives {
+        log.events.push(TelemetryEvent::Death { t, level: level.0, lives_left: lives.0 });
+    }
+    if lives.0 != log.last_lives {
+        log.last_lives = lives.0;
+    }
+}
+
+fn write_run_report(time: Res<Time>, mut finished_events: EventReader<RunFinished>, mut log: ResMut<RunLog>) {
+    for run in finished_events.read() {
+        let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+        let report = RunReport {
+            finished_at,
+            score: run.score,
+            level: run.level,
+            reduced_speed: run.reduced_speed,
+            duration: time.elapsed_seconds() - log.started_at,
+            events: &log.events,
+        };
+        let path = log.dir.join(format!("run-{finished_at}.json"));
+        match serde_json::to_string_pretty(&report) {
+            Ok(contents) => match std::fs::write(&path, contents) {
+                Ok(()) => println!("Run telemetry written to {}", path.display()),
+                Err(error) => warn!("Could not write {}: {error}", path.display()),
+            },
+            Err(error) => warn!("Could not serialize run telemetry: {error}"),
+        }
+        log.events.clear();
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::input::{ActionState, PlayerAction};
+use super::profiles::CurrentProfile;
+use super::{
+    on_wave_cleared, storage, GameAssets, GameMode, GameplaySet, Level, Locale, RestartRun, WaveClock, WaveCleared, WaveWon,
+};
+
+// Time attack (--time-attack): the first five levels against the clock. Each cleared wave adds a split
+// (its wave clock time, so intros and pauses don't count), and the run is over once wave five falls.
+// Best total and best split per wave are kept per profile in time_attack.ron.
+pub const WAVES: u32 = 5;
+const BEST_TIMES_FILE: &str = "time_attack.ron";
+
+#[derive(Resource, Default)]
+pub struct TimeAttackRun {
+    splits: Vec<f32>,
+    // Each split against the best for that wave before this run
+    deltas: Vec<Option<f32>>,
+    new_best: bool,
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct BestTimes {
+    total: Option<f32>,
+    // Fastest time seen for each wave, not necessarily from the same run
+    splits: Vec<f32>,
+}
+
+#[derive(Component)]
+struct TimeAttackText;
+
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        let best = BestTimes::load(app.world.resource::<CurrentProfile>());
+        app.insert_resource(best)
+            .init_resource::<TimeAttackRun>()
+            .add_systems(Startup, setup_time_attack_ui.run_if(time_attack_mode))
+            .add_systems(
+                Update,
+                (reload_best_times, reset_time_attack_run, record_split.after(on_wave_cleared))
+                    .chain()
+                    .in_set(GameplaySet::Collision)
+                    .run_if(time_attack_mode),
+            )
+            .add_systems(Update, retry_finished_run.in_set(GameplaySet::Input).run_if(time_attack_mode))
+            .add_systems(Update, update_time_attack_text.in_set(GameplaySet::Ui).run_if(time_attack_mode));
+    }
+}
+
+pub fn time_attack_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::TimeAttack
+}
+
+impl TimeAttackRun {
+    pub fn finished(&self) -> bool {
+        self.splits.len() as u32 >= WAVES
+    }
+
+    fn total(&self) -> f32 {
+        self.splits.iter().sum()
+    }
+}
+
+impl BestTimes {
+    fn load(profile: &CurrentProfile) -> Self {
+        storage::load_ron(&profile.data_file(BEST_TIMES_FILE))
+    }
+
+    fn save(&self, profile: &CurrentProfile) {
+        storage::save_ron(&profile.data_file(BEST_TIMES_FILE), self, "best times");
+    }
+}
+
+fn reload_best_times(profile: Res<CurrentProfile>, mut best: ResMut<BestTimes>) {
+    if profile.is_changed() && !profile.is_added() {
+        *best = BestTimes::load(&profile);
+    }
+}
+
+fn reset_time_attack_run(mut restart_events: EventReader<RestartRun>, mut run: ResMut<TimeAttackRun>) {
+    if restart_events.read().count() > 0 {
+        *run = TimeAttackRun::default();
+    }
+}
+
+fn record_split(
+    mut cleared_events: EventReader<WaveCleared>,
+    clock: Res<WaveClock>,
+    level: Res<Level>,
+    profile: Res<CurrentProfile>,
+    mut run: ResMut<TimeAttackRun>,
+    mut best: ResMut<BestTimes>,
+) {
+    // One split per level, however many clear events arrive
+    if cleared_events.read().count() == 0 || run.splits.len() as u32 >= level.0 {
+        return;
+    }
+    let delta = best.splits.get(run.splits.len()).map(|best_split| clock.elapsed - best_split);
+    run.splits.push(clock.elapsed);
+    run.deltas.push(delta);
+    println!("Wave {} split: {:.2}s", run.splits.len(), clock.elapsed);
+    if !run.finished() {
+        return;
+    }
+
+    let total = run.total();
+    for (index, split) in run.splits.iter().enumerate() {
+        match best.splits.get_mut(index) {
+            Some(best_split) => *best_split = best_split.min(*split),
+            None => best.splits.push(*split),
+        }
+    }
+    run.new_best = best.total.is_none_or(|best_total| total < best_total);
+    if run.new_best {
+        best.total = Some(total);
+    }
+    best.save(&profile);
+    println!("Time attack finished in {:.2}s", total);
+}
+
+// Next level is off after the last wave, so R starts the attempt over instead
+fn retry_finished_run(action_state: Res<ActionState>, run: Res<TimeAttackRun>, mut restart_events: EventWriter<RestartRun>) {
+    if run.finished() && action_state.just_pressed(PlayerAction::Restart) {
+        restart_events.send(RestartRun);
+    }
+}
+
+fn setup_time_attack_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 24.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        TimeAttackText,
+    ));
+}
+
+// Running total on top, then a line per finished wave with the difference to the best split
+fn update_time_attack_text(
+    clock: Res<WaveClock>,
+    wave_won: Res<WaveWon>,
+    run: Res<TimeAttackRun>,
+    best: Res<BestTimes>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<TimeAttackText>>,
+) {
+    let running = if wave_won.0 || run.finished() { 0.0 } else { clock.elapsed };
+    let mut lines = vec![format!("{} {:.2}s", locale.get("time_attack.time"), run.total() + running)];
+    for (index, (split, delta)) in run.splits.iter().zip(&run.deltas).enumerate() {
+        let mut line = format!("{} {}: {:.2}s", locale.get("time_attack.wave"), index + 1, split);
+        if let Some(delta) = delta {
+            line.push_str(&format!(" ({:+.2})", delta));
+        }
+        lines.push(line);
+    }
+    if run.finished() && run.new_best {
+        lines.push(locale.get("time_attack.new_best").to_string());
+    } else if let Some(best_total) = best.total {
+        lines.push(format!("{} {:.2}s", locale.get("time_attack.best"), best_total));
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}